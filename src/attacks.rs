@@ -0,0 +1,588 @@
+//! Attack lookups for every piece kind and line tables between squares
+//!
+//! Knight, king and pawn attacks are computed at compile time. Slider attacks use magic
+//! bitboards whose tables, along with the between and line tables, are built once on
+//! first use.
+
+use crate::bitboard::Bitboard;
+use crate::types::{PieceColor, Square};
+use std::sync::OnceLock;
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+    (1, 2),
+    (1, -2),
+    (-1, 2),
+    (-1, -2),
+];
+
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+static KNIGHT_ATTACKS: [Bitboard; 64] = step_table(&KNIGHT_OFFSETS);
+
+static KING_ATTACKS: [Bitboard; 64] = step_table(&KING_OFFSETS);
+
+static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    step_table(&[(1, -1), (1, 1)]),
+    step_table(&[(-1, -1), (-1, 1)]),
+];
+
+// Multipliers that map every relevant occupancy of a slider's mask to a unique table
+// index, found with a fixed-shift brute force search over sparse random numbers.
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080004008801020,
+    0x0840092002c03000,
+    0x1900200010400900,
+    0x0880100008000480,
+    0x4200100420080200,
+    0x8100020100080400,
+    0x0200040110886200,
+    0x0200008040220411,
+    0x0404800084400220,
+    0x0000401000402000,
+    0x0086001081220440,
+    0x0408800800100280,
+    0x000a001201040820,
+    0x8848800200840080,
+    0x4001000100040200,
+    0x0442000102105084,
+    0x9080010020804100,
+    0x0040404000201009,
+    0x0000808010002009,
+    0x2200090021d00100,
+    0x0008008008040080,
+    0x0004004002010040,
+    0x0011040008015042,
+    0x00000a0001768104,
+    0x0000800080204009,
+    0x2010004140002001,
+    0x9800200280100080,
+    0x1000100080080080,
+    0x0442000a00049020,
+    0x2100040080020080,
+    0x0800120400900148,
+    0x0010040a00128541,
+    0x2800804000800030,
+    0x1010002000400041,
+    0x4000200011004100,
+    0x0610008410800800,
+    0x0400802402800800,
+    0xc100020080800400,
+    0x0002000802000401,
+    0x0182085882000401,
+    0x0220204000808000,
+    0x2860100040024022,
+    0x0001002004110040,
+    0x99101042000a0020,
+    0x0004080004008080,
+    0x0010040002008080,
+    0x2012004881020004,
+    0x8300842444820011,
+    0x0088403882010200,
+    0x0820400080210100,
+    0x0110910040a00300,
+    0x0801100280080480,
+    0x0242009008200600,
+    0x1002000489500200,
+    0x0040800200010080,
+    0x0091800041000080,
+    0x0000209300488001,
+    0x04c1002414824001,
+    0x020020000b001041,
+    0x7000100004200901,
+    0x8002002004100802,
+    0x30010002084c0007,
+    0x0888221800813004,
+    0x4000002840840112,
+];
+
+const BISHOP_MAGICS: [u64; 64] = [
+    0xa010041108003100,
+    0x006082020a002900,
+    0x6810010619200000,
+    0x08281a0520000408,
+    0x0001104001000400,
+    0x0018901008048400,
+    0x00040a0210245280,
+    0x000200210808a402,
+    0x9140048410821200,
+    0x0800091010820041,
+    0x20504804832202c0,
+    0x0100091401081000,
+    0x8021011140000012,
+    0x0810020804450400,
+    0x208b0542109008a2,
+    0x0080084a08040204,
+    0x0040e2a80811244c,
+    0x2505022008008108,
+    0x0430220100420040,
+    0x010a040420220040,
+    0x1105000290400000,
+    0x0093001200822120,
+    0x4000a62048043004,
+    0x280120048a015004,
+    0x006090002a020814,
+    0x44042000240800d0,
+    0x01102800040a4400,
+    0x1004080080220040,
+    0x0001001011004024,
+    0x0010044000805040,
+    0x0914041200820100,
+    0x0004821012821480,
+    0x0024040500c05021,
+    0x0088611002080200,
+    0x0116080a00040020,
+    0x4000020080080080,
+    0x2450450140840040,
+    0x0000880201484100,
+    0x0222020404020092,
+    0x8081110600002e00,
+    0x2842101105000801,
+    0x1100809008001025,
+    0x00020202221c0400,
+    0x0422014022009020,
+    0x0210046102100c00,
+    0xc004008082029102,
+    0x00aa461801101200,
+    0x0404080080201108,
+    0x020542108c205002,
+    0x0410544804100100,
+    0x0040910841100000,
+    0x0400200042021100,
+    0x00004204850400c0,
+    0x0200100410a42102,
+    0x1040020801210102,
+    0x0805040410420000,
+    0x2884804130100200,
+    0x800c262201242000,
+    0x1058000194108800,
+    0x0014221054420204,
+    0x0104000012a02200,
+    0x0200881003300100,
+    0x0140400202840100,
+    0x0402020801010201,
+];
+
+/// Magic lookup parameters for a single square
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied.0 & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct SliderTables {
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    attacks: Vec<Bitboard>,
+}
+
+struct LineTables {
+    between: Vec<Bitboard>,
+    line: Vec<Bitboard>,
+}
+
+static SLIDERS: OnceLock<SliderTables> = OnceLock::new();
+
+static LINES: OnceLock<LineTables> = OnceLock::new();
+
+/// Returns the squares a pawn of the given color on `square` attacks
+pub fn pawn_attacks(color: PieceColor, square: Square) -> Bitboard {
+    PAWN_ATTACKS[color.index()][square.index()]
+}
+
+/// Returns the squares a knight on `square` attacks
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_ATTACKS[square.index()]
+}
+
+/// Returns the squares a king on `square` attacks
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_ATTACKS[square.index()]
+}
+
+/// Returns the squares a bishop on `square` attacks, stopping at the first occupied
+/// square in each direction
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let tables = sliders();
+    tables.attacks[tables.bishop[square.index()].index(occupied)]
+}
+
+/// Returns the squares a rook on `square` attacks, stopping at the first occupied
+/// square in each direction
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let tables = sliders();
+    tables.attacks[tables.rook[square.index()].index(occupied)]
+}
+
+/// Returns the squares a queen on `square` attacks
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    bishop_attacks(square, occupied) | rook_attacks(square, occupied)
+}
+
+/// Returns the squares strictly between `a` and `b` if they share a rank, file or
+/// diagonal, otherwise an empty bitboard
+pub fn between(a: Square, b: Square) -> Bitboard {
+    lines().between[a.index() * 64 + b.index()]
+}
+
+/// Returns every square on the rank, file or diagonal through `a` and `b`, including
+/// both of them, or an empty bitboard if they are not aligned
+pub fn line(a: Square, b: Square) -> Bitboard {
+    lines().line[a.index() * 64 + b.index()]
+}
+
+const fn step_table(offsets: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [Bitboard::EMPTY; 64];
+    let mut index = 0;
+    while index < 64 {
+        let rank = (index / 8) as i8;
+        let file = (index % 8) as i8;
+        let mut bits = 0;
+        let mut i = 0;
+        while i < offsets.len() {
+            let (r, f) = (rank + offsets[i].0, file + offsets[i].1);
+            if r >= 0 && r < 8 && f >= 0 && f < 8 {
+                bits |= 1 << (r * 8 + f);
+            }
+            i += 1;
+        }
+        table[index] = Bitboard(bits);
+        index += 1;
+    }
+    table
+}
+
+/// Walks every direction from `square`, including the first occupied square hit
+/// This is the slow reference the magic tables are built from.
+fn ray_attacks(square: Square, occupied: Bitboard, directions: &[(i8, i8)]) -> Bitboard {
+    let mut attacks = Bitboard::EMPTY;
+    for &(dr, df) in directions {
+        let mut current = square.offset(dr, df);
+        while let Some(target) = current {
+            attacks.insert(target);
+            if occupied.contains(target) {
+                break;
+            }
+            current = target.offset(dr, df);
+        }
+    }
+    attacks
+}
+
+/// Returns the squares whose occupancy can block a slider on `square`, which excludes the
+/// last square in each direction since a piece there never blocks anything
+fn relevant_mask(square: Square, directions: &[(i8, i8)]) -> u64 {
+    let mut mask = 0;
+    for &(dr, df) in directions {
+        let mut current = square.offset(dr, df);
+        while let Some(target) = current {
+            current = target.offset(dr, df);
+            if current.is_some() {
+                mask |= 1 << target.index();
+            }
+        }
+    }
+    mask
+}
+
+fn build_magics(
+    directions: &[(i8, i8)],
+    magics: &[u64; 64],
+    attacks: &mut Vec<Bitboard>,
+) -> Vec<Magic> {
+    (0..64)
+        .map(|index| {
+            let square = Square::from_index(index).unwrap();
+            let mask = relevant_mask(square, directions);
+            let magic = Magic {
+                mask,
+                magic: magics[index],
+                shift: 64 - mask.count_ones(),
+                offset: attacks.len(),
+            };
+            attacks.resize(attacks.len() + (1 << mask.count_ones()), Bitboard::EMPTY);
+
+            // Enumerate every subset of the mask with the carry-rippler trick
+            let mut subset: u64 = 0;
+            loop {
+                let occupied = Bitboard(subset);
+                let expected = ray_attacks(square, occupied, directions);
+                // Sliders always attack at least one square, so an empty slot is unused and a
+                // different one means the magic maps two occupancies with different attacks
+                // to the same slot
+                let slot = &mut attacks[magic.index(occupied)];
+                assert!(
+                    *slot == Bitboard::EMPTY || *slot == expected,
+                    "magic collision for square {}",
+                    square
+                );
+                *slot = expected;
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+
+            magic
+        })
+        .collect()
+}
+
+fn sliders() -> &'static SliderTables {
+    SLIDERS.get_or_init(|| {
+        let mut attacks = Vec::new();
+        let rook = build_magics(&ROOK_DIRECTIONS, &ROOK_MAGICS, &mut attacks);
+        let bishop = build_magics(&BISHOP_DIRECTIONS, &BISHOP_MAGICS, &mut attacks);
+        SliderTables {
+            rook,
+            bishop,
+            attacks,
+        }
+    })
+}
+
+fn lines() -> &'static LineTables {
+    LINES.get_or_init(|| {
+        let mut between = vec![Bitboard::EMPTY; 64 * 64];
+        let mut line = vec![Bitboard::EMPTY; 64 * 64];
+
+        for a in (0..64).filter_map(Square::from_index) {
+            for b in (0..64).filter_map(Square::from_index) {
+                let (a_bb, b_bb) = (Bitboard::from_square(a), Bitboard::from_square(b));
+                let index = a.index() * 64 + b.index();
+                if a == b {
+                    continue;
+                }
+
+                for attacks in [rook_attacks, bishop_attacks] {
+                    if attacks(a, Bitboard::EMPTY).contains(b) {
+                        between[index] = attacks(a, b_bb) & attacks(b, a_bb);
+                        line[index] = (attacks(a, Bitboard::EMPTY) & attacks(b, Bitboard::EMPTY))
+                            | a_bb
+                            | b_bb;
+                    }
+                }
+            }
+        }
+
+        LineTables { between, line }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(s: &str) -> Square {
+        s.parse().unwrap()
+    }
+
+    fn squares(list: &[&str]) -> Bitboard {
+        list.iter().map(|s| square(s)).collect()
+    }
+
+    fn all_squares() -> impl Iterator<Item = Square> {
+        (0..64).filter_map(Square::from_index)
+    }
+
+    /// Naive stepping reference for the compile time tables
+    fn naive_step_attacks(square: Square, offsets: &[(i8, i8)]) -> Bitboard {
+        offsets
+            .iter()
+            .filter_map(|&(dr, df)| square.offset(dr, df))
+            .collect()
+    }
+
+    #[test]
+    fn test_pawn_attacks() {
+        assert_eq!(
+            pawn_attacks(PieceColor::White, square("e4")),
+            squares(&["d5", "f5"])
+        );
+        assert_eq!(
+            pawn_attacks(PieceColor::Black, square("a7")),
+            squares(&["b6"])
+        );
+        assert_eq!(
+            pawn_attacks(PieceColor::White, square("h8")),
+            Bitboard::EMPTY
+        );
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        assert_eq!(knight_attacks(square("a1")), squares(&["b3", "c2"]));
+        assert_eq!(knight_attacks(square("e4")).count(), 8);
+        assert_eq!(king_attacks(square("h1")), squares(&["g1", "g2", "h2"]));
+        assert_eq!(king_attacks(square("e4")).count(), 8);
+    }
+
+    #[test]
+    fn test_step_tables_match_naive() {
+        for sq in all_squares() {
+            assert_eq!(knight_attacks(sq), naive_step_attacks(sq, &KNIGHT_OFFSETS));
+            assert_eq!(king_attacks(sq), naive_step_attacks(sq, &KING_OFFSETS));
+            assert_eq!(
+                pawn_attacks(PieceColor::White, sq),
+                naive_step_attacks(sq, &[(1, -1), (1, 1)])
+            );
+            assert_eq!(
+                pawn_attacks(PieceColor::Black, sq),
+                naive_step_attacks(sq, &[(-1, -1), (-1, 1)])
+            );
+        }
+    }
+
+    #[test]
+    fn test_slider_attacks() {
+        let occupied = squares(&["e6", "c4", "g2"]);
+        assert_eq!(
+            rook_attacks(square("e4"), occupied),
+            squares(&["e5", "e6", "e3", "e2", "e1", "d4", "c4", "f4", "g4", "h4"])
+        );
+        assert_eq!(
+            bishop_attacks(square("e4"), occupied),
+            squares(&[
+                "d5", "c6", "b7", "a8", "f5", "g6", "h7", "d3", "c2", "b1", "f3", "g2"
+            ])
+        );
+        assert_eq!(
+            queen_attacks(square("e4"), occupied),
+            rook_attacks(square("e4"), occupied) | bishop_attacks(square("e4"), occupied)
+        );
+        assert_eq!(rook_attacks(square("a1"), Bitboard::EMPTY).count(), 14);
+    }
+
+    #[test]
+    fn test_slider_tables_match_naive() {
+        // xorshift64, so the occupancies are the same on every run
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for sq in all_squares() {
+            for _ in 0..200 {
+                // Mix sparse and dense boards, including the slider's own square
+                let occupied = Bitboard(random() & random() | random() & random() & random());
+                assert_eq!(
+                    rook_attacks(sq, occupied),
+                    ray_attacks(sq, occupied, &ROOK_DIRECTIONS),
+                    "rook on {} with\n{}",
+                    sq,
+                    occupied
+                );
+                assert_eq!(
+                    bishop_attacks(sq, occupied),
+                    ray_attacks(sq, occupied, &BISHOP_DIRECTIONS),
+                    "bishop on {} with\n{}",
+                    sq,
+                    occupied
+                );
+            }
+            assert_eq!(
+                rook_attacks(sq, Bitboard::FULL),
+                ray_attacks(sq, Bitboard::FULL, &ROOK_DIRECTIONS)
+            );
+            assert_eq!(
+                bishop_attacks(sq, Bitboard::EMPTY),
+                ray_attacks(sq, Bitboard::EMPTY, &BISHOP_DIRECTIONS)
+            );
+        }
+    }
+
+    #[test]
+    fn test_slider_tables_every_blocker_subset() {
+        for sq in all_squares() {
+            for directions in [&ROOK_DIRECTIONS[..], &BISHOP_DIRECTIONS[..]] {
+                let mask = relevant_mask(sq, directions);
+                let mut subset: u64 = 0;
+                loop {
+                    let occupied = Bitboard(subset);
+                    let attacks = if directions == &ROOK_DIRECTIONS[..] {
+                        rook_attacks(sq, occupied)
+                    } else {
+                        bishop_attacks(sq, occupied)
+                    };
+                    assert_eq!(attacks, ray_attacks(sq, occupied, directions), "{}", sq);
+                    subset = subset.wrapping_sub(mask) & mask;
+                    if subset == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_between_and_line() {
+        assert_eq!(between(square("a1"), square("d4")), squares(&["b2", "c3"]));
+        assert_eq!(between(square("e1"), square("e4")), squares(&["e2", "e3"]));
+        assert_eq!(between(square("e1"), square("e2")), Bitboard::EMPTY);
+        assert_eq!(between(square("a1"), square("b3")), Bitboard::EMPTY);
+        assert_eq!(between(square("c3"), square("c3")), Bitboard::EMPTY);
+
+        assert_eq!(
+            line(square("b2"), square("c3")),
+            squares(&["a1", "b2", "c3", "d4", "e5", "f6", "g7", "h8"])
+        );
+        assert_eq!(line(square("d1"), square("d5")), Bitboard::file(3));
+        assert_eq!(line(square("a1"), square("b3")), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_line_tables_match_naive() {
+        for a in all_squares() {
+            for b in all_squares() {
+                // Walk from `a` towards `b` one step at a time along any shared line
+                let dr = (b.rank() as i8 - a.rank() as i8).signum();
+                let df = (b.file() as i8 - a.file() as i8).signum();
+                let aligned = a != b
+                    && (a.rank() == b.rank()
+                        || a.file() == b.file()
+                        || a.rank().abs_diff(b.rank()) == a.file().abs_diff(b.file()));
+
+                let mut expected_between = Bitboard::EMPTY;
+                let mut expected_line = Bitboard::EMPTY;
+                if aligned {
+                    let mut current = a.offset(dr, df).unwrap();
+                    while current != b {
+                        expected_between.insert(current);
+                        current = current.offset(dr, df).unwrap();
+                    }
+                    expected_line = ray_attacks(a, Bitboard::EMPTY, &[(dr, df), (-dr, -df)])
+                        | Bitboard::from_square(a);
+                }
+
+                assert_eq!(between(a, b), expected_between, "{} {}", a, b);
+                assert_eq!(line(a, b), expected_line, "{} {}", a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::types::Square;
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    ops::{
+        BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr, Sub,
+        SubAssign,
+    },
+};
+
+/// A set of squares stored as a 64-bit integer
+/// Bit `n` represents the square with index `n`, so a1 is the least significant bit
+/// and h8 the most significant one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(u64::MAX);
+    /// The 32 dark squares, including a1 and h8
+    pub const DARK_SQUARES: Bitboard = Bitboard(0xaa55_aa55_aa55_aa55);
+    /// The 32 light squares, including h1 and a8
+    pub const LIGHT_SQUARES: Bitboard = Bitboard(!0xaa55_aa55_aa55_aa55);
+
+    /// Returns a bitboard containing only the given square
+    pub const fn from_square(square: Square) -> Bitboard {
+        Bitboard(1 << square.index())
+    }
+
+    /// Returns a bitboard of all squares on the given rank (0-7)
+    pub const fn rank(rank: u8) -> Bitboard {
+        Bitboard(0xff << (rank * 8))
+    }
+
+    /// Returns a bitboard of all squares on the given file (0-7)
+    pub const fn file(file: u8) -> Bitboard {
+        Bitboard(0x0101_0101_0101_0101 << file)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & (1 << square.index()) != 0
+    }
+
+    pub fn insert(&mut self, square: Square) {
+        self.0 |= 1 << square.index();
+    }
+
+    pub fn remove(&mut self, square: Square) {
+        self.0 &= !(1 << square.index());
+    }
+
+    /// Returns the number of squares in the set
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Returns true if the set contains more than one square
+    pub const fn more_than_one(self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    /// Returns the square with the lowest index, if any
+    pub fn first(self) -> Option<Square> {
+        Square::from_index(self.0.trailing_zeros() as usize)
+    }
+
+    /// Removes and returns the square with the lowest index, if any
+    pub fn pop_first(&mut self) -> Option<Square> {
+        let square = self.first()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    /// Shifts every square one rank up, dropping those on the eighth rank
+    pub const fn north(self) -> Bitboard {
+        Bitboard(self.0 << 8)
+    }
+
+    /// Shifts every square one rank down, dropping those on the first rank
+    pub const fn south(self) -> Bitboard {
+        Bitboard(self.0 >> 8)
+    }
+
+    /// Shifts every square one file right, dropping those on the h-file
+    pub const fn east(self) -> Bitboard {
+        Bitboard((self.0 & !Bitboard::file(7).0) << 1)
+    }
+
+    /// Shifts every square one file left, dropping those on the a-file
+    pub const fn west(self) -> Bitboard {
+        Bitboard((self.0 & !Bitboard::file(0).0) >> 1)
+    }
+
+    /// Returns an iterator over the squares in the set, in ascending index order
+    pub fn squares(self) -> BitboardIter {
+        BitboardIter(self)
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        Bitboard::from_square(square)
+    }
+}
+
+/// Iterator over the squares of a `Bitboard`
+#[derive(Debug, Clone)]
+pub struct BitboardIter(Bitboard);
+
+impl Iterator for BitboardIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        self.0.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for BitboardIter {}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = BitboardIter;
+
+    fn into_iter(self) -> BitboardIter {
+        self.squares()
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Bitboard {
+        let mut bitboard = Bitboard::EMPTY;
+        for square in iter {
+            bitboard.insert(square);
+        }
+        bitboard
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl $trait for Bitboard {
+            type Output = Bitboard;
+
+            fn $method(self, rhs: Bitboard) -> Bitboard {
+                Bitboard(self.0 $op rhs.0)
+            }
+        }
+
+        impl $assign_trait for Bitboard {
+            fn $assign_method(&mut self, rhs: Bitboard) {
+                self.0 = self.0 $op rhs.0;
+            }
+        }
+    };
+}
+
+impl_binary_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+impl_binary_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+impl_binary_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl Sub for Bitboard {
+    type Output = Bitboard;
+
+    /// Returns the squares in `self` that are not in `rhs`
+    fn sub(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 & !rhs.0)
+    }
+}
+
+impl SubAssign for Bitboard {
+    fn sub_assign(&mut self, rhs: Bitboard) {
+        self.0 &= !rhs.0;
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Shl<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0.checked_shl(rhs).unwrap_or(0))
+    }
+}
+
+impl Shr<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0.checked_shr(rhs).unwrap_or(0))
+    }
+}
+
+impl Display for Bitboard {
+    /// Renders the set as a board diagram with `x` for squares in the set
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        for rank in (0..8).rev() {
+            write!(f, "{} ", rank + 1)?;
+            for file in 0..8 {
+                let square = Square::from_rank_and_file(rank, file).unwrap();
+                let char = if self.contains(square) { 'x' } else { '.' };
+                write!(f, "{} ", char)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "  a b c d e f g h")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(s: &str) -> Square {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_bitboard_insert_remove_contains() {
+        let mut bb = Bitboard::EMPTY;
+        assert!(bb.is_empty());
+        bb.insert(square("e4"));
+        bb.insert(square("a1"));
+        assert!(bb.contains(square("e4")));
+        assert!(bb.contains(square("a1")));
+        assert!(!bb.contains(square("h8")));
+        assert_eq!(bb.count(), 2);
+        bb.remove(square("e4"));
+        assert!(!bb.contains(square("e4")));
+        assert_eq!(bb, Bitboard(1));
+    }
+
+    #[test]
+    fn test_bitboard_set_operations() {
+        let a = Bitboard::rank(0);
+        let b = Bitboard::file(0);
+        assert_eq!((a & b).squares().collect::<Vec<_>>(), vec![square("a1")]);
+        assert_eq!((a | b).count(), 15);
+        assert_eq!((a ^ b).count(), 14);
+        assert_eq!((a - b).count(), 7);
+        assert_eq!((!a).count(), 56);
+        assert_eq!(Bitboard::FULL.count(), 64);
+    }
+
+    #[test]
+    fn test_bitboard_iteration() {
+        let bb: Bitboard = [square("h8"), square("c3"), square("a1")]
+            .into_iter()
+            .collect();
+        let squares: Vec<_> = bb.into_iter().collect();
+        assert_eq!(squares, vec![square("a1"), square("c3"), square("h8")]);
+        assert_eq!(bb.squares().len(), 3);
+        assert_eq!(bb.first(), Some(square("a1")));
+        assert_eq!(Bitboard::EMPTY.first(), None);
+        assert!(bb.more_than_one());
+        assert!(!Bitboard::from_square(square("d4")).more_than_one());
+    }
+
+    #[test]
+    fn test_bitboard_shifts() {
+        let bb = Bitboard::from_square(square("a4")) | Bitboard::from_square(square("h5"));
+        assert_eq!(
+            bb.north(),
+            Bitboard::from_square(square("a5")) | Bitboard::from_square(square("h6"))
+        );
+        assert_eq!(
+            bb.south(),
+            Bitboard::from_square(square("a3")) | Bitboard::from_square(square("h4"))
+        );
+        // Squares shifted off the side of the board are dropped instead of wrapping
+        assert_eq!(bb.east(), Bitboard::from_square(square("b4")));
+        assert_eq!(bb.west(), Bitboard::from_square(square("g5")));
+        assert_eq!(Bitboard::rank(7).north(), Bitboard::EMPTY);
+        assert_eq!(Bitboard(1) << 9, Bitboard::from_square(square("b2")));
+        assert_eq!(Bitboard(1) << 64, Bitboard::EMPTY);
+        assert_eq!(Bitboard::rank(7) >> 56, Bitboard::rank(0));
+    }
+
+    #[test]
+    fn test_bitboard_display() {
+        let bb = Bitboard::rank(1) | Bitboard::from_square(square("e4"));
+        let expected = concat!(
+            "8 . . . . . . . . \n",
+            "7 . . . . . . . . \n",
+            "6 . . . . . . . . \n",
+            "5 . . . . . . . . \n",
+            "4 . . . . x . . . \n",
+            "3 . . . . . . . . \n",
+            "2 x x x x x x x x \n",
+            "1 . . . . . . . . \n",
+            "  a b c d e f g h\n",
+        );
+        assert_eq!(bb.to_string(), expected);
+    }
+}
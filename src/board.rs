@@ -1,34 +1,83 @@
+use crate::attacks::pawn_attacks;
+use crate::bitboard::Bitboard;
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::moves::Move;
 use crate::types::{Piece, PieceColor, PieceKind, Square};
+use crate::validate::PositionProblem;
+use crate::zobrist;
 use std::{
     error::Error,
     fmt::{Display, Error as FmtError, Formatter},
     str::FromStr,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct CasltingRights {
-    white_kingside: bool,
-    white_queenside: bool,
-    black_kingside: bool,
-    black_queenside: bool,
+/// Conventions for writing the en passant field of a FEN string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EnPassantMode {
+    /// Write the target square after every double pawn push, as FIDE FEN specifies
+    #[default]
+    Always,
+    /// Write the target square only if a legal en passant capture exists, as Polyglot
+    /// and lichess do
+    Legal,
 }
 
-impl Default for CasltingRights {
-    fn default() -> Self {
-        CasltingRights {
-            white_kingside: false,
-            white_queenside: false,
-            black_kingside: false,
-            black_queenside: false,
-        }
+/// The state discarded by `Board::make_move` that is needed to take the move back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    mv: Move,
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    zobrist_key: u64,
+}
+
+impl Undo {
+    /// Returns the move this token takes back
+    pub fn mv(&self) -> Move {
+        self.mv
+    }
+
+    /// Returns the piece captured by the move, if any
+    pub fn captured(&self) -> Option<Piece> {
+        self.captured
+    }
+
+    /// Returns the Zobrist key of the position before the move
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist_key
     }
 }
 
+/// The state discarded by `Board::make_null_move` that is needed to take it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NullMoveUndo {
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    zobrist_key: u64,
+}
+
+/// A chess position
+/// Pieces are stored both as a square-indexed mailbox for fast lookups of single squares
+/// and as per-color and per-kind bitboards for move generation and evaluation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
     squares: [Option<Piece>; 64],
+    by_color: [Bitboard; 2],
+    by_kind: [Bitboard; 6],
     side_to_move: PieceColor,
-    castling_rights: CasltingRights,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    zobrist_key: u64,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Board {
@@ -36,8 +85,14 @@ impl Board {
     pub fn new() -> Board {
         Board {
             squares: [None; 64],
+            by_color: [Bitboard::EMPTY; 2],
+            by_kind: [Bitboard::EMPTY; 6],
             side_to_move: PieceColor::White,
-            castling_rights: CasltingRights::default(),
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist_key: 0,
         }
     }
 
@@ -50,9 +105,125 @@ impl Board {
     }
 
     pub fn set_piece_at(&mut self, square: Square, piece: Option<Piece>) {
+        // Moving pawns around can change whether the en passant square counts for the key
+        self.zobrist_key ^= self.en_passant_zobrist_key();
+        self.replace_piece(square, piece);
+        self.zobrist_key ^= self.en_passant_zobrist_key();
+    }
+
+    /// Puts a piece on a square, keeping the mailbox, bitboards and piece keys in sync
+    fn replace_piece(&mut self, square: Square, piece: Option<Piece>) {
+        if let Some(old) = self.squares[square.index()] {
+            self.by_color[old.color().index()].remove(square);
+            self.by_kind[old.kind().index()].remove(square);
+            self.zobrist_key ^= zobrist::piece_key(old, square);
+        }
+        if let Some(new) = piece {
+            self.by_color[new.color().index()].insert(square);
+            self.by_kind[new.kind().index()].insert(square);
+            self.zobrist_key ^= zobrist::piece_key(new, square);
+        }
         self.squares[square.index()] = piece;
     }
 
+    /// Returns the 64-bit Zobrist hash of the position
+    /// Covers the pieces, side to move, castling rights and the en passant file, the
+    /// latter only when a pawn of the side to move could capture there. The clocks
+    /// are not included, so transpositions reached at different move numbers share a key.
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist_key
+    }
+
+    /// Computes the Zobrist hash from scratch
+    /// Always equal to `Board::zobrist_key`, which is updated incrementally instead.
+    pub fn compute_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+        for square in self.occupied() {
+            key ^= zobrist::piece_key(self.piece_at(square).unwrap(), square);
+        }
+        if self.side_to_move == PieceColor::Black {
+            key ^= zobrist::black_to_move_key();
+        }
+        key ^ self.castling_rights.zobrist_key() ^ self.en_passant_zobrist_key()
+    }
+
+    /// Returns the en passant part of the Zobrist hash, which is only non-zero when a
+    /// pawn of the side to move attacks the en passant square
+    fn en_passant_zobrist_key(&self) -> u64 {
+        match self.en_passant {
+            Some(square)
+                if !(pawn_attacks(self.side_to_move.opposite(), square)
+                    & self.pieces(self.side_to_move, PieceKind::Pawn))
+                .is_empty() =>
+            {
+                zobrist::en_passant_key(square.file())
+            }
+            _ => 0,
+        }
+    }
+
+    /// Returns the squares of all pieces of the given color and kind
+    pub fn pieces(&self, color: PieceColor, kind: PieceKind) -> Bitboard {
+        self.by_color[color.index()] & self.by_kind[kind.index()]
+    }
+
+    /// Returns the squares of all pieces of the given color
+    pub fn by_color(&self, color: PieceColor) -> Bitboard {
+        self.by_color[color.index()]
+    }
+
+    /// Returns the squares of all pieces of the given kind, of either color
+    pub fn by_kind(&self, kind: PieceKind) -> Bitboard {
+        self.by_kind[kind.index()]
+    }
+
+    /// Returns the squares of all pieces on the board
+    pub fn occupied(&self) -> Bitboard {
+        self.by_color[0] | self.by_color[1]
+    }
+
+    /// Returns the square of the king of the given color, if there is one on the board
+    pub fn king_square(&self, color: PieceColor) -> Option<Square> {
+        self.pieces(color, PieceKind::King).first()
+    }
+
+    /// Returns the castling rights of both players
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// Replaces the castling rights, keeping the Zobrist key in sync
+    /// Rights are not checked against the placement of kings and rooks, see `validate`.
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.zobrist_key ^= self.castling_rights.zobrist_key() ^ rights.zobrist_key();
+        self.castling_rights = rights;
+    }
+
+    /// Returns true if the given color may still castle on the king side
+    pub fn can_castle_kingside(&self, color: PieceColor) -> bool {
+        self.castling_rights.has(color, CastlingSide::Kingside)
+    }
+
+    /// Returns true if the given color may still castle on the queen side
+    pub fn can_castle_queenside(&self, color: PieceColor) -> bool {
+        self.castling_rights.has(color, CastlingSide::Queenside)
+    }
+
+    /// Returns the en passant target square, if the last move was a double pawn push
+    pub fn en_passant_square(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Returns the number of halfmoves since the last capture or pawn move
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Returns the fullmove number, starting at 1 and incremented after each Black move
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
     pub fn startpos() -> Self {
         let mut board = Self::new();
         let back_rank = [
@@ -89,12 +260,224 @@ impl Board {
             );
         }
 
+        board.castling_rights = CastlingRights::ALL;
+        board.zobrist_key = board.compute_zobrist_key();
+
         board
     }
 
+    /// Plays a move on the board, updating the side to move, castling rights,
+    /// en passant square and clocks
+    /// The move must be pseudo-legal in the current position, e.g. taken from
+    /// `Board::legal_moves`. Returns the token `Board::unmake_move` needs to restore
+    /// the previous position.
+    pub fn make_move(&mut self, mv: Move) -> Undo {
+        let piece = self
+            .piece_at(mv.from())
+            .expect("make_move called without a piece on the from square");
+        let color = piece.color();
+
+        let captured_square = if mv.is_en_passant() {
+            Square::from_rank_and_file(mv.from().rank(), mv.to().file()).unwrap()
+        } else {
+            mv.to()
+        };
+
+        let undo = Undo {
+            mv,
+            captured: self.piece_at(captured_square),
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key,
+        };
+
+        // Take out the parts of the key that depend on more than the moved pieces,
+        // they are added back once the move is complete
+        self.zobrist_key ^= self.en_passant_zobrist_key() ^ self.castling_rights.zobrist_key();
+
+        self.replace_piece(captured_square, None);
+        self.replace_piece(mv.from(), None);
+        let moved = mv.promotion().map_or(piece, |kind| Piece::new(color, kind));
+        self.replace_piece(mv.to(), Some(moved));
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(mv);
+            let rook = self.piece_at(rook_from);
+            self.replace_piece(rook_from, None);
+            self.replace_piece(rook_to, rook);
+        }
+
+        self.castling_rights.revoke_square(mv.from());
+        self.castling_rights.revoke_square(mv.to());
+
+        // The en passant target is the square the pawn skipped over
+        self.en_passant = if mv.is_double_push() {
+            Square::from_rank_and_file((mv.from().rank() + mv.to().rank()) / 2, mv.from().file())
+        } else {
+            None
+        };
+
+        if piece.kind() == PieceKind::Pawn || undo.captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = color.opposite();
+
+        self.zobrist_key ^= zobrist::black_to_move_key()
+            ^ self.castling_rights.zobrist_key()
+            ^ self.en_passant_zobrist_key();
+
+        undo
+    }
+
+    /// Takes back the move described by `undo`, restoring the board exactly as it was
+    /// before the matching `Board::make_move` call
+    /// Moves must be taken back in the reverse order they were made.
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let mv = undo.mv;
+        let piece = self
+            .piece_at(mv.to())
+            .expect("unmake_move called without a piece on the to square");
+        let color = piece.color();
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(mv);
+            let rook = self.piece_at(rook_to);
+            self.replace_piece(rook_to, None);
+            self.replace_piece(rook_from, rook);
+        }
+
+        let original = match mv.promotion() {
+            Some(_) => Piece::new(color, PieceKind::Pawn),
+            None => piece,
+        };
+        self.replace_piece(mv.to(), None);
+        self.replace_piece(mv.from(), Some(original));
+
+        let captured_square = if mv.is_en_passant() {
+            Square::from_rank_and_file(mv.from().rank(), mv.to().file()).unwrap()
+        } else {
+            mv.to()
+        };
+        self.replace_piece(captured_square, undo.captured);
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.zobrist_key = undo.zobrist_key;
+        if color == PieceColor::Black {
+            self.fullmove_number -= 1;
+        }
+        self.side_to_move = color;
+    }
+
+    /// Passes the turn to the other side without moving a piece, as done by null-move
+    /// pruning in the search
+    /// The side to move must not be in check. The halfmove clock restarts, so positions
+    /// before the null move never count as repetitions of positions after it.
+    pub(crate) fn make_null_move(&mut self) -> NullMoveUndo {
+        let undo = NullMoveUndo {
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key,
+        };
+
+        self.zobrist_key ^= self.en_passant_zobrist_key() ^ zobrist::black_to_move_key();
+        self.en_passant = None;
+        self.halfmove_clock = 0;
+        if self.side_to_move == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opposite();
+
+        undo
+    }
+
+    /// Takes back a null move made by `Board::make_null_move`
+    pub(crate) fn unmake_null_move(&mut self, undo: NullMoveUndo) {
+        self.side_to_move = self.side_to_move.opposite();
+        if self.side_to_move == PieceColor::Black {
+            self.fullmove_number -= 1;
+        }
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.zobrist_key = undo.zobrist_key;
+    }
+
     pub fn from_fen(fen: &str) -> Result<Self, ParseFenError> {
         Self::from_str(fen)
     }
+
+    /// Returns the board as a canonical six-field FEN string
+    /// Parsing the result with `Board::from_fen` yields an identical board.
+    pub fn to_fen(&self) -> String {
+        self.to_fen_with(EnPassantMode::Always)
+    }
+
+    /// Returns the board as a six-field FEN string, writing the en passant field as
+    /// selected by `mode`
+    pub fn to_fen_with(&self, mode: EnPassantMode) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let square = Square::from_rank_and_file(rank, file).unwrap();
+                match self.piece_at(square) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.to_fen());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        let side_to_move = match self.side_to_move {
+            PieceColor::White => 'w',
+            PieceColor::Black => 'b',
+        };
+        let en_passant = match mode {
+            EnPassantMode::Always => self.en_passant,
+            EnPassantMode::Legal => self.legal_en_passant_square(),
+        };
+        let en_passant = en_passant.map_or("-".to_string(), |square| square.to_string());
+
+        format!(
+            "{} {} {} {} {} {}",
+            fen,
+            side_to_move,
+            self.castling_rights.to_fen(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+/// Returns the from and to squares of the rook for a castling move
+fn castling_rook_squares(mv: Move) -> (Square, Square) {
+    let rank = mv.from().rank();
+    let (from_file, to_file) = if mv.to().file() == 6 { (7, 5) } else { (0, 3) };
+    (
+        Square::from_rank_and_file(rank, from_file).unwrap(),
+        Square::from_rank_and_file(rank, to_file).unwrap(),
+    )
 }
 
 impl Display for Board {
@@ -113,12 +496,104 @@ impl Display for Board {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseFenError(String);
+/// The kind of problem found while parsing a FEN string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseFenErrorKind {
+    /// Fewer than the two required fields (placement and side to move)
+    MissingField,
+    /// More than the six fields defined by FEN
+    TooManyFields,
+    /// The placement field does not contain exactly 8 ranks
+    BadRankCount,
+    /// A rank describes more than 8 files
+    RankOverflow,
+    /// A rank describes fewer than 8 files
+    RankUnderflow,
+    /// A character in the placement field is neither a piece nor an empty run of 1-8
+    UnknownPiece(char),
+    /// The side to move is not `w` or `b`
+    BadSideToMove,
+    /// The castling field is not `-` or a set of unique `KQkq` characters
+    BadCastling,
+    /// The en passant field is not `-` or a square on the correct rank
+    BadEnPassant,
+    /// The halfmove clock is not a non-negative number
+    BadHalfmoveClock,
+    /// The fullmove number is not a positive number
+    BadFullmoveNumber,
+    /// The FEN is well formed but describes an impossible position, only reported in
+    /// strict mode
+    InvalidPosition(PositionProblem),
+}
+
+impl Display for ParseFenErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            ParseFenErrorKind::MissingField => write!(f, "not enough fields"),
+            ParseFenErrorKind::TooManyFields => write!(f, "too many fields"),
+            ParseFenErrorKind::BadRankCount => write!(f, "incorrect number of ranks"),
+            ParseFenErrorKind::RankOverflow => write!(f, "rank has more than 8 squares"),
+            ParseFenErrorKind::RankUnderflow => write!(f, "rank has fewer than 8 squares"),
+            ParseFenErrorKind::UnknownPiece(c) => write!(f, "unknown piece character '{}'", c),
+            ParseFenErrorKind::BadSideToMove => write!(f, "invalid side to move"),
+            ParseFenErrorKind::BadCastling => write!(f, "invalid castling availability"),
+            ParseFenErrorKind::BadEnPassant => write!(f, "invalid en passant square"),
+            ParseFenErrorKind::BadHalfmoveClock => write!(f, "invalid halfmove clock"),
+            ParseFenErrorKind::BadFullmoveNumber => write!(f, "invalid fullmove number"),
+            ParseFenErrorKind::InvalidPosition(problem) => write!(f, "{}", problem),
+        }
+    }
+}
+
+/// Error returned when a FEN string cannot be parsed
+/// Carries the kind of problem, the index of the field it was found in (0 for the piece
+/// placement through 5 for the fullmove number) and the byte offset into the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFenError {
+    kind: ParseFenErrorKind,
+    field: usize,
+    offset: usize,
+    fen: String,
+}
+
+impl ParseFenError {
+    pub(crate) fn new(kind: ParseFenErrorKind, field: usize, offset: usize, fen: &str) -> Self {
+        ParseFenError {
+            kind,
+            field,
+            offset,
+            fen: fen.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> ParseFenErrorKind {
+        self.kind
+    }
+
+    /// Returns the index (0-5) of the FEN field the error was found in
+    pub fn field(&self) -> usize {
+        self.field
+    }
+
+    /// Returns the byte offset into the FEN string where the error was found
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
 
 impl Display for ParseFenError {
+    /// Renders the error followed by the input with a caret under the offending character:
+    ///
+    /// ```text
+    /// Invalid FEN: unknown piece character 'X' at offset 15
+    /// 8/8/8/8/8/8/8/7X w
+    ///                ^
+    /// ```
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "ParseFenError: {}", self.0)
+        writeln!(f, "Invalid FEN: {} at offset {}", self.kind, self.offset)?;
+        writeln!(f, "{}", self.fen)?;
+        let column = self.fen[..self.offset].chars().count();
+        write!(f, "{:>width$}", "^", width = column + 1)
     }
 }
 
@@ -129,89 +604,135 @@ impl FromStr for Board {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut board = Board::new();
+        let error = |kind, field, offset| ParseFenError::new(kind, field, offset, s);
 
-        let parts: Vec<&str> = s.split_whitespace().collect();
-
-        if parts.len() < 2 {
-            return Err(ParseFenError("Invalid FEN: Not enough parts".to_string()));
+        // Split on whitespace while remembering where each field starts
+        let mut fields: Vec<(usize, &str)> = Vec::new();
+        let mut field_start = None;
+        for (offset, c) in s.char_indices() {
+            match (c.is_whitespace(), field_start) {
+                (false, None) => field_start = Some(offset),
+                (true, Some(start)) => {
+                    fields.push((start, &s[start..offset]));
+                    field_start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = field_start {
+            fields.push((start, &s[start..]));
         }
 
-        let ranks: Vec<&str> = parts[0].split('/').collect();
-
-        if ranks.len() != 8 {
-            return Err(ParseFenError(
-                "Invalid FEN: Incorrect number of ranks".to_string(),
+        if fields.len() < 2 {
+            return Err(error(
+                ParseFenErrorKind::MissingField,
+                fields.len(),
+                s.len(),
             ));
         }
 
-        // Each rank must expand to exactly 8 files.
-        for (rank_index, rank) in ranks.iter().enumerate() {
-            let mut file_count: usize = 0;
+        if fields.len() > 6 {
+            return Err(error(ParseFenErrorKind::TooManyFields, 6, fields[6].0));
+        }
 
-            for c in rank.chars() {
-                if matches!(c, '1'..='8') {
-                    file_count += c.to_digit(10).unwrap() as usize;
-                } else {
-                    file_count += 1;
-                }
-            }
+        let (placement_start, placement) = fields[0];
+        let mut rank_index: u8 = 0;
+        let mut file_index: u8 = 0;
 
-            if file_count != 8 {
-                return Err(ParseFenError(format!(
-                    "Invalid FEN: Rank {} does not expand to 8 squares",
-                    8 - rank_index
-                )));
-            }
-        }
+        for (index, c) in placement.char_indices() {
+            let offset = placement_start + index;
 
-        for (rank_index, rank_str) in ranks.iter().enumerate() {
-            // file index 0 to 7
-            let mut file_index = 0;
-            for c in rank_str.chars() {
-                // If adding the next piece/squares would go past the last file,
-                // bail out with an explicit error.
-                if file_index > 7 {
-                    return Err(ParseFenError(format!(
-                        "Invalid FEN: Too many squares in rank {}",
-                        8 - rank_index
-                    )));
+            if c == '/' {
+                if file_index < 8 {
+                    return Err(error(ParseFenErrorKind::RankUnderflow, 0, offset));
+                }
+                if rank_index == 7 {
+                    return Err(error(ParseFenErrorKind::BadRankCount, 0, offset));
                 }
+                rank_index += 1;
+                file_index = 0;
+                continue;
+            }
 
-                if matches!(c, '1'..='8') {
-                    let empty_squares = c.to_digit(10).unwrap() as usize;
+            match c {
+                '0'..='9' => {
+                    let empty_squares = c.to_digit(10).unwrap() as u8;
+                    // Adding the empty squares would go past the last file
+                    if file_index + empty_squares > 8 {
+                        return Err(error(ParseFenErrorKind::RankOverflow, 0, offset));
+                    }
                     file_index += empty_squares;
-                } else {
-                    let piece = Piece::from_fen(c).ok_or(ParseFenError(format!(
-                        "Invalid FEN: Unknown piece character '{}'",
-                        c
-                    )))?;
-                    let square = Square::from_rank_and_file(7 - rank_index as u8, file_index as u8)
-                        .ok_or(ParseFenError(
-                            "Invalid FEN: Square out of bounds".to_string(),
-                        ))?;
+                }
+                _ => {
+                    let piece = Piece::from_fen(c)
+                        .ok_or_else(|| error(ParseFenErrorKind::UnknownPiece(c), 0, offset))?;
+                    if file_index > 7 {
+                        return Err(error(ParseFenErrorKind::RankOverflow, 0, offset));
+                    }
+                    let square = Square::from_rank_and_file(7 - rank_index, file_index).unwrap();
                     board.set_piece_at(square, Some(piece));
                     file_index += 1;
                 }
             }
+        }
 
-            if file_index != 8 {
-                return Err(ParseFenError(format!(
-                    "Invalid FEN: Rank {} does not end on file 8",
-                    8 - rank_index
-                )));
-            }
+        let placement_end = placement_start + placement.len();
+        if file_index < 8 {
+            return Err(error(ParseFenErrorKind::RankUnderflow, 0, placement_end));
+        }
+        if rank_index != 7 {
+            return Err(error(ParseFenErrorKind::BadRankCount, 0, placement_end));
         }
 
-        board.side_to_move = match parts[1] {
+        let (offset, field) = fields[1];
+        board.side_to_move = match field {
             "w" => PieceColor::White,
             "b" => PieceColor::Black,
-            _ => {
-                return Err(ParseFenError(
-                    "Invalid FEN: Invalid side to move".to_string(),
-                ));
-            }
+            _ => return Err(error(ParseFenErrorKind::BadSideToMove, 1, offset)),
         };
 
+        // The remaining fields are optional, older FENs often stop after the side to move
+        if let Some(&(offset, field)) = fields.get(2) {
+            board.castling_rights = CastlingRights::from_fen(field)
+                .map_err(|e| error(ParseFenErrorKind::BadCastling, 2, offset + e.index()))?;
+        }
+
+        if let Some(&(offset, field)) = fields.get(3) {
+            board.en_passant = match field {
+                "-" => None,
+                _ => {
+                    let square = Square::from_str(field)
+                        .map_err(|_| error(ParseFenErrorKind::BadEnPassant, 3, offset))?;
+
+                    // The target square sits behind the pawn that just moved two squares
+                    let expected_rank = match board.side_to_move {
+                        PieceColor::White => 5,
+                        PieceColor::Black => 2,
+                    };
+                    if square.rank() != expected_rank {
+                        return Err(error(ParseFenErrorKind::BadEnPassant, 3, offset));
+                    }
+
+                    Some(square)
+                }
+            };
+        }
+
+        if let Some(&(offset, field)) = fields.get(4) {
+            board.halfmove_clock = field
+                .parse()
+                .map_err(|_| error(ParseFenErrorKind::BadHalfmoveClock, 4, offset))?;
+        }
+
+        if let Some(&(offset, field)) = fields.get(5) {
+            board.fullmove_number = match field.parse() {
+                Ok(n) if n >= 1 => n,
+                _ => return Err(error(ParseFenErrorKind::BadFullmoveNumber, 5, offset)),
+            };
+        }
+
+        board.zobrist_key = board.compute_zobrist_key();
+
         Ok(board)
     }
 }
@@ -239,6 +760,43 @@ mod tests {
         assert_eq!(board.piece_at(square), Some(piece));
     }
 
+    #[test]
+    fn test_board_bitboards() {
+        let mut board = Board::startpos();
+        assert_eq!(board.occupied().count(), 32);
+        assert_eq!(board.by_color(PieceColor::White), Bitboard(0xffff));
+        assert_eq!(
+            board.by_kind(PieceKind::Pawn),
+            Bitboard::rank(1) | Bitboard::rank(6)
+        );
+        assert_eq!(
+            board.pieces(PieceColor::Black, PieceKind::Knight),
+            Bitboard::from_square("b8".parse().unwrap())
+                | Bitboard::from_square("g8".parse().unwrap())
+        );
+        assert_eq!(
+            board.king_square(PieceColor::White),
+            Some("e1".parse().unwrap())
+        );
+
+        // Replacing a piece updates the bitboards of both the old and new piece
+        let square = "d1".parse().unwrap();
+        board.set_piece_at(square, Some(Piece::new(PieceColor::Black, PieceKind::Rook)));
+        assert!(
+            !board
+                .pieces(PieceColor::White, PieceKind::Queen)
+                .contains(square)
+        );
+        assert!(
+            board
+                .pieces(PieceColor::Black, PieceKind::Rook)
+                .contains(square)
+        );
+        board.set_piece_at(square, None);
+        assert!(!board.occupied().contains(square));
+        assert_eq!(board.occupied().count(), 31);
+    }
+
     #[test]
     fn test_board_startpos() {
         let board = Board::startpos();
@@ -348,4 +906,442 @@ mod tests {
         let result = Board::from_str(invalid_fen);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_board_from_full_fen() {
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w Kq e6 0 2";
+        let board = Board::from_str(fen).unwrap();
+        assert!(board.can_castle_kingside(PieceColor::White));
+        assert!(!board.can_castle_queenside(PieceColor::White));
+        assert!(!board.can_castle_kingside(PieceColor::Black));
+        assert!(board.can_castle_queenside(PieceColor::Black));
+        assert_eq!(board.en_passant_square(), Some("e6".parse().unwrap()));
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 2);
+    }
+
+    #[test]
+    fn test_board_from_fen_optional_fields() {
+        let board = Board::from_str("8/8/8/3k4/8/8/4K3/8 b").unwrap();
+        assert!(!board.can_castle_kingside(PieceColor::White));
+        assert!(!board.can_castle_queenside(PieceColor::Black));
+        assert_eq!(board.en_passant_square(), None);
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 1);
+
+        let board = Board::from_str("8/8/8/3k4/8/8/4K3/8 b - - 12").unwrap();
+        assert_eq!(board.halfmove_clock(), 12);
+        assert_eq!(board.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn test_board_set_castling_rights() {
+        let mut board = Board::startpos();
+        assert_eq!(board.castling_rights(), CastlingRights::ALL);
+
+        let rights = CastlingRights::single(PieceColor::Black, CastlingSide::Queenside);
+        board.set_castling_rights(rights);
+        assert_eq!(board.castling_rights(), rights);
+        assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w q - 0 1"
+        );
+
+        // Moving the rook off its corner revokes the right
+        board.make_move(find_move(&board, "g1f3"));
+        board.make_move(find_move(&board, "b8c6"));
+        board.make_move(find_move(&board, "f3g1"));
+        board.make_move(find_move(&board, "a8b8"));
+        assert!(board.castling_rights().is_empty());
+    }
+
+    #[test]
+    fn test_board_startpos_castling_rights() {
+        let board = Board::startpos();
+        for color in [PieceColor::White, PieceColor::Black] {
+            assert!(board.can_castle_kingside(color));
+            assert!(board.can_castle_queenside(color));
+        }
+        assert_eq!(
+            board,
+            Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
+        );
+    }
+
+    // A mix of opening, middlegame, endgame and edge-case positions used for round-trip tests
+    const ROUND_TRIP_FENS: [&str; 24] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 b - - 99 150",
+        "8/8/8/3k4/8/8/4K3/8 b - - 0 1",
+        "8/8/8/2Qk4/8/8/4Pq2/8 b - - 0 1",
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 5 20",
+        "r3k2r/8/8/8/8/8/8/R3K2R w Qk - 0 1",
+        "8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1",
+        "8/8/8/KPp4r/8/8/8/7k w - c6 0 2",
+        "rnbqkb1r/ppppp1pp/7n/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/P7/8/8/8/8/7p/k6K w - - 0 60",
+        "QQQQQQQQ/8/8/8/8/8/8/k6K b - - 0 1",
+        "pppppppp/pppppppp/8/8/8/8/PPPPPPPP/PPPPPPPP w - - 0 1",
+        "8/8/8/8/8/8/8/8 w - - 0 1",
+    ];
+
+    #[test]
+    fn test_board_to_fen_startpos() {
+        assert_eq!(
+            Board::startpos().to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_board_to_fen_fills_optional_fields() {
+        let board = Board::from_fen("8/8/8/3k4/8/8/4K3/8 b").unwrap();
+        assert_eq!(board.to_fen(), "8/8/8/3k4/8/8/4K3/8 b - - 0 1");
+    }
+
+    #[test]
+    fn test_board_to_fen_canonical() {
+        // Canonical inputs are reproduced byte for byte
+        for fen in ROUND_TRIP_FENS {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_board_fen_round_trip() {
+        for fen in ROUND_TRIP_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_parse_fen_error_kinds() {
+        for (fen, kind, field, offset) in [
+            ("", ParseFenErrorKind::MissingField, 0, 0),
+            ("8/8/8/8/8/8/8/8", ParseFenErrorKind::MissingField, 1, 15),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 1 x",
+                ParseFenErrorKind::TooManyFields,
+                6,
+                26,
+            ),
+            ("8/8/8/8/8/8/8 w", ParseFenErrorKind::BadRankCount, 0, 13),
+            (
+                "8/8/8/8/8/8/8/8/8 w",
+                ParseFenErrorKind::BadRankCount,
+                0,
+                15,
+            ),
+            (
+                "8/8/8/8/8/8/8/7pp w",
+                ParseFenErrorKind::RankOverflow,
+                0,
+                16,
+            ),
+            ("8/8/8/8/8/8/8/72 w", ParseFenErrorKind::RankOverflow, 0, 15),
+            ("8/8/8/8/8/8/8/p9 w", ParseFenErrorKind::RankOverflow, 0, 15),
+            ("8/8/7/8/8/8/8/8 w", ParseFenErrorKind::RankUnderflow, 0, 5),
+            ("8/8/8/8/8/8/8/7 w", ParseFenErrorKind::RankUnderflow, 0, 15),
+            (
+                "8/8/8/8/8/8/8/7X w",
+                ParseFenErrorKind::UnknownPiece('X'),
+                0,
+                15,
+            ),
+            ("9/8/8/8/8/8/8/8 w", ParseFenErrorKind::RankOverflow, 0, 0),
+            ("8/8/8/8/8/8/8/8 x", ParseFenErrorKind::BadSideToMove, 1, 16),
+            (
+                "8/8/8/8/8/8/8/8 w KQx",
+                ParseFenErrorKind::BadCastling,
+                2,
+                20,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - e3",
+                ParseFenErrorKind::BadEnPassant,
+                3,
+                20,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - - x 1",
+                ParseFenErrorKind::BadHalfmoveClock,
+                4,
+                22,
+            ),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 0",
+                ParseFenErrorKind::BadFullmoveNumber,
+                5,
+                24,
+            ),
+        ] {
+            let err = Board::from_str(fen).unwrap_err();
+            assert_eq!(err.kind(), kind, "{}", fen);
+            assert_eq!(err.field(), field, "{}", fen);
+            assert_eq!(err.offset(), offset, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_parse_fen_error_display() {
+        let err = Board::from_str("8/8/8/8/8/8/8/7X w").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            concat!(
+                "Invalid FEN: unknown piece character 'X' at offset 15\n",
+                "8/8/8/8/8/8/8/7X w\n",
+                "               ^",
+            )
+        );
+
+        let err = Board::from_str("8/8/8/8/8/8/8/8").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            concat!(
+                "Invalid FEN: not enough fields at offset 15\n",
+                "8/8/8/8/8/8/8/8\n",
+                "               ^",
+            )
+        );
+    }
+
+    fn find_move(board: &Board, uci: &str) -> Move {
+        *board
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_string() == uci)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_make_move_updates_state() {
+        let mut board = Board::startpos();
+        board.make_move(find_move(&board, "e2e4"));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        board.make_move(find_move(&board, "g8f6"));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+    }
+
+    #[test]
+    fn test_make_move_special_moves() {
+        for (fen, uci, expected) in [
+            // Castling moves the rook and removes both rights of the side
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10",
+                "e1g1",
+                "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 4 10",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10",
+                "e8c8",
+                "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 4 11",
+            ),
+            // Capturing a rook on its starting square removes the right
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                "a1a8",
+                "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1",
+            ),
+            // En passant removes the captured pawn
+            (
+                "rnbqkb1r/ppppp1pp/7n/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "e5f6",
+                "rnbqkb1r/ppppp1pp/5P1n/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+            ),
+            // Promotion replaces the pawn
+            (
+                "1n5k/P7/8/8/8/8/8/7K w - - 5 40",
+                "a7b8n",
+                "1N5k/8/8/8/8/8/8/7K b - - 0 40",
+            ),
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            board.make_move(find_move(&board, uci));
+            assert_eq!(board.to_fen(), expected);
+        }
+    }
+
+    #[test]
+    fn test_unmake_move_restores_board() {
+        // Every legal move, and every reply, must be taken back exactly
+        for fen in ROUND_TRIP_FENS {
+            let mut board = Board::from_fen(fen).unwrap();
+            let original = board.clone();
+            for mv in board.legal_moves() {
+                let undo = board.make_move(mv);
+                let after = board.clone();
+                for reply in board.legal_moves() {
+                    let reply_undo = board.make_move(reply);
+                    board.unmake_move(reply_undo);
+                    assert_eq!(board, after, "{} {} {}", fen, mv, reply);
+                }
+                board.unmake_move(undo);
+                assert_eq!(board, original, "{} {}", fen, mv);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zobrist_key_incremental() {
+        // Walk two plies deep from every test position, checking the incremental key
+        // against a full recomputation after every make and unmake
+        for fen in ROUND_TRIP_FENS {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.zobrist_key(), board.compute_zobrist_key(), "{}", fen);
+            for mv in board.legal_moves() {
+                let undo = board.make_move(mv);
+                assert_eq!(
+                    board.zobrist_key(),
+                    board.compute_zobrist_key(),
+                    "{} {}",
+                    fen,
+                    mv
+                );
+                for reply in board.legal_moves() {
+                    let reply_undo = board.make_move(reply);
+                    assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+                    board.unmake_move(reply_undo);
+                }
+                board.unmake_move(undo);
+                assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zobrist_key_transposition() {
+        let play = |moves: &[&str]| {
+            let mut board = Board::startpos();
+            for uci in moves {
+                board.make_move(find_move(&board, uci));
+            }
+            board
+        };
+
+        let a = play(&["g1f3", "g8f6", "b1c3"]);
+        let b = play(&["b1c3", "g8f6", "g1f3"]);
+        assert_eq!(a.zobrist_key(), b.zobrist_key());
+
+        // Clocks are not part of the key
+        let back = play(&["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(back.zobrist_key(), Board::startpos().zobrist_key());
+        assert_ne!(a.zobrist_key(), Board::startpos().zobrist_key());
+
+        // The side to move is part of the key
+        let mut black = Board::startpos();
+        black.side_to_move = PieceColor::Black;
+        assert_ne!(black.compute_zobrist_key(), Board::startpos().zobrist_key());
+    }
+
+    #[test]
+    fn test_zobrist_key_en_passant() {
+        // Without a pawn able to capture, the en passant square does not affect the key
+        let with_ep = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        let without_ep = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(with_ep.zobrist_key(), without_ep.zobrist_key());
+
+        let with_ep = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let without_ep = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(with_ep.zobrist_key(), without_ep.zobrist_key());
+
+        // Placing a capturing pawn next to the pushed pawn brings the file into the key
+        let mut board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        board.set_piece_at(
+            "f4".parse().unwrap(),
+            Some(Piece::new(PieceColor::Black, PieceKind::Pawn)),
+        );
+        assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+        assert_ne!(
+            board.zobrist_key(),
+            Board::from_fen("4k3/8/8/8/4Pp2/8/8/4K3 b - - 0 1")
+                .unwrap()
+                .zobrist_key()
+        );
+    }
+
+    #[test]
+    fn test_null_move() {
+        let fen = "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let undo = board.make_null_move();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/3pP3/8/8/4K3 w - - 0 2");
+        assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+
+        board.unmake_null_move(undo);
+        assert_eq!(board, Board::from_fen(fen).unwrap());
+    }
+
+    #[test]
+    fn test_board_to_fen_en_passant_modes() {
+        let mut board = Board::startpos();
+        board.make_move(find_move(&board, "e2e4"));
+        assert_eq!(
+            board.to_fen_with(EnPassantMode::Always),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert_eq!(board.to_fen(), board.to_fen_with(EnPassantMode::Always));
+        assert_eq!(
+            board.to_fen_with(EnPassantMode::Legal),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        let fen = "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.to_fen_with(EnPassantMode::Legal), fen);
+    }
+
+    #[test]
+    fn test_board_from_fen_invalid_castling() {
+        for fen in [
+            "8/8/8/8/8/8/8/8 w X",
+            "8/8/8/8/8/8/8/8 w KK",
+            "8/8/8/8/8/8/8/8 w K-",
+        ] {
+            assert!(Board::from_str(fen).is_err(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_board_from_fen_invalid_en_passant() {
+        for fen in [
+            "8/8/8/8/8/8/8/8 w - e9",
+            "8/8/8/8/8/8/8/8 w - e3", // white to move, target must be on rank 6
+            "8/8/8/8/8/8/8/8 b - e6", // black to move, target must be on rank 3
+        ] {
+            assert!(Board::from_str(fen).is_err(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_board_from_fen_invalid_clocks() {
+        for fen in [
+            "8/8/8/8/8/8/8/8 w - - x 1",
+            "8/8/8/8/8/8/8/8 w - - -1 1",
+            "8/8/8/8/8/8/8/8 w - - 0 0",
+            "8/8/8/8/8/8/8/8 w - - 0 y",
+            "8/8/8/8/8/8/8/8 w - - 0 1 extra",
+        ] {
+            assert!(Board::from_str(fen).is_err(), "{}", fen);
+        }
+    }
 }
@@ -0,0 +1,293 @@
+use crate::types::{PieceColor, Square};
+use crate::zobrist;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// The side of the board a king castles towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    Kingside,
+    Queenside,
+}
+
+/// The set of castling rights still available to both players
+/// Each of the four rights is one bit, in FEN order `KQkq`, so rights combine with the
+/// usual set operators: `|` for union, `&` for intersection and `-` for difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights(u8);
+
+impl CastlingRights {
+    pub const NONE: CastlingRights = CastlingRights(0);
+    pub const ALL: CastlingRights = CastlingRights(0b1111);
+
+    /// Returns the set containing only the right of `color` to castle on `side`
+    pub const fn single(color: PieceColor, side: CastlingSide) -> CastlingRights {
+        let side_index = match side {
+            CastlingSide::Kingside => 0,
+            CastlingSide::Queenside => 1,
+        };
+        CastlingRights(1 << (color.index() * 2 + side_index))
+    }
+
+    /// Returns the set containing both rights of `color`
+    pub const fn both(color: PieceColor) -> CastlingRights {
+        CastlingRights(0b11 << (color.index() * 2))
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every right in `other` is also in this set
+    pub const fn contains(self, other: CastlingRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if `color` may still castle on `side`
+    pub const fn has(self, color: PieceColor, side: CastlingSide) -> bool {
+        self.contains(CastlingRights::single(color, side))
+    }
+
+    pub fn insert(&mut self, color: PieceColor, side: CastlingSide) {
+        *self |= CastlingRights::single(color, side);
+    }
+
+    pub fn remove(&mut self, color: PieceColor, side: CastlingSide) {
+        *self -= CastlingRights::single(color, side);
+    }
+
+    /// Removes both rights of `color`, as when its king moves
+    pub fn revoke_color(&mut self, color: PieceColor) {
+        *self -= CastlingRights::both(color);
+    }
+
+    /// Removes any right that depends on a king or rook standing on the given square
+    /// Called with both squares of every move, so moving a king or rook as well as
+    /// capturing a rook on its starting square revokes the matching rights.
+    pub fn revoke_square(&mut self, square: Square) {
+        *self -= match square.index() {
+            0 => CastlingRights::single(PieceColor::White, CastlingSide::Queenside),
+            4 => CastlingRights::both(PieceColor::White),
+            7 => CastlingRights::single(PieceColor::White, CastlingSide::Kingside),
+            56 => CastlingRights::single(PieceColor::Black, CastlingSide::Queenside),
+            60 => CastlingRights::both(PieceColor::Black),
+            63 => CastlingRights::single(PieceColor::Black, CastlingSide::Kingside),
+            _ => CastlingRights::NONE,
+        };
+    }
+
+    /// Returns the Zobrist key contribution of the rights
+    pub(crate) fn zobrist_key(self) -> u64 {
+        (0..4)
+            .filter(|index| self.0 & (1 << index) != 0)
+            .fold(0, |key, index| key ^ zobrist::castling_key(index))
+    }
+
+    /// Parses the castling availability field of a FEN string, e.g. `KQkq` or `-`
+    pub fn from_fen(field: &str) -> Result<CastlingRights, ParseCastlingRightsError> {
+        let mut rights = CastlingRights::NONE;
+
+        if field == "-" {
+            return Ok(rights);
+        }
+        if field.is_empty() {
+            return Err(ParseCastlingRightsError { index: 0 });
+        }
+
+        for (index, c) in field.char_indices() {
+            let right = match c {
+                'K' => CastlingRights::single(PieceColor::White, CastlingSide::Kingside),
+                'Q' => CastlingRights::single(PieceColor::White, CastlingSide::Queenside),
+                'k' => CastlingRights::single(PieceColor::Black, CastlingSide::Kingside),
+                'q' => CastlingRights::single(PieceColor::Black, CastlingSide::Queenside),
+                _ => return Err(ParseCastlingRightsError { index }),
+            };
+
+            // Each right may only be listed once
+            if rights.contains(right) {
+                return Err(ParseCastlingRightsError { index });
+            }
+            rights |= right;
+        }
+
+        Ok(rights)
+    }
+
+    /// Formats the rights as a FEN castling availability field, `-` when no rights remain
+    pub fn to_fen(self) -> String {
+        let field: String = ['K', 'Q', 'k', 'q']
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| self.0 & (1 << index) != 0)
+            .map(|(_, c)| c)
+            .collect();
+
+        if field.is_empty() {
+            "-".to_string()
+        } else {
+            field
+        }
+    }
+}
+
+/// Error returned when a castling availability field cannot be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseCastlingRightsError {
+    index: usize,
+}
+
+impl ParseCastlingRightsError {
+    /// Returns the byte index of the offending character within the field
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Display for ParseCastlingRightsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(
+            f,
+            "Invalid castling rights: unexpected character at index {}",
+            self.index
+        )
+    }
+}
+
+impl Error for ParseCastlingRightsError {}
+
+impl FromStr for CastlingRights {
+    type Err = ParseCastlingRightsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CastlingRights::from_fen(s)
+    }
+}
+
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+impl BitOr for CastlingRights {
+    type Output = CastlingRights;
+
+    fn bitor(self, rhs: CastlingRights) -> CastlingRights {
+        CastlingRights(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for CastlingRights {
+    fn bitor_assign(&mut self, rhs: CastlingRights) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for CastlingRights {
+    type Output = CastlingRights;
+
+    fn bitand(self, rhs: CastlingRights) -> CastlingRights {
+        CastlingRights(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for CastlingRights {
+    fn bitand_assign(&mut self, rhs: CastlingRights) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for CastlingRights {
+    type Output = CastlingRights;
+
+    fn sub(self, rhs: CastlingRights) -> CastlingRights {
+        CastlingRights(self.0 & !rhs.0)
+    }
+}
+
+impl SubAssign for CastlingRights {
+    fn sub_assign(&mut self, rhs: CastlingRights) {
+        self.0 &= !rhs.0;
+    }
+}
+
+impl Not for CastlingRights {
+    type Output = CastlingRights;
+
+    fn not(self) -> CastlingRights {
+        CastlingRights(!self.0 & CastlingRights::ALL.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_castling_rights_fen_round_trip() {
+        for field in ["-", "K", "Qk", "KQkq", "Kq", "kq"] {
+            let rights: CastlingRights = field.parse().unwrap();
+            assert_eq!(rights.to_string(), field);
+        }
+        assert_eq!(
+            "qkQK".parse::<CastlingRights>().unwrap(),
+            CastlingRights::ALL
+        );
+
+        for (field, index) in [("", 0), ("KK", 1), ("KQx", 2), ("-K", 0)] {
+            assert_eq!(
+                CastlingRights::from_fen(field).map_err(|e| e.index()),
+                Err(index),
+                "{}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_castling_rights_queries() {
+        let rights: CastlingRights = "Kq".parse().unwrap();
+        assert!(rights.has(PieceColor::White, CastlingSide::Kingside));
+        assert!(!rights.has(PieceColor::White, CastlingSide::Queenside));
+        assert!(!rights.has(PieceColor::Black, CastlingSide::Kingside));
+        assert!(rights.has(PieceColor::Black, CastlingSide::Queenside));
+        assert!(CastlingRights::ALL.contains(rights));
+        assert!(!rights.contains(CastlingRights::ALL));
+        assert!(CastlingRights::NONE.is_empty());
+    }
+
+    #[test]
+    fn test_castling_rights_set_operations() {
+        let white = CastlingRights::both(PieceColor::White);
+        let black = CastlingRights::both(PieceColor::Black);
+        assert_eq!(white | black, CastlingRights::ALL);
+        assert_eq!(white & black, CastlingRights::NONE);
+        assert_eq!(CastlingRights::ALL - white, black);
+        assert_eq!(!white, black);
+        assert_eq!(!CastlingRights::NONE, CastlingRights::ALL);
+
+        let mut rights = CastlingRights::NONE;
+        rights.insert(PieceColor::Black, CastlingSide::Kingside);
+        rights.insert(PieceColor::White, CastlingSide::Queenside);
+        assert_eq!(rights.to_fen(), "Qk");
+        rights.remove(PieceColor::Black, CastlingSide::Kingside);
+        assert_eq!(rights.to_fen(), "Q");
+    }
+
+    #[test]
+    fn test_castling_rights_revocation() {
+        let mut rights = CastlingRights::ALL;
+        rights.revoke_square("h8".parse().unwrap());
+        assert_eq!(rights.to_fen(), "KQq");
+        rights.revoke_square("e4".parse().unwrap());
+        assert_eq!(rights.to_fen(), "KQq");
+        rights.revoke_square("e1".parse().unwrap());
+        assert_eq!(rights.to_fen(), "q");
+        rights.revoke_color(PieceColor::Black);
+        assert!(rights.is_empty());
+    }
+}
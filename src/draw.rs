@@ -0,0 +1,83 @@
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::types::PieceKind;
+
+impl Board {
+    /// Returns true if neither side has enough material left to checkmate
+    /// This covers king against king, a single minor piece against a bare king, and
+    /// positions where all remaining minor pieces are bishops on squares of one color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy_or_pawns = self.by_kind(PieceKind::Queen)
+            | self.by_kind(PieceKind::Rook)
+            | self.by_kind(PieceKind::Pawn);
+        if !heavy_or_pawns.is_empty() {
+            return false;
+        }
+
+        let knights = self.by_kind(PieceKind::Knight);
+        let bishops = self.by_kind(PieceKind::Bishop);
+        if (knights | bishops).count() <= 1 {
+            return true;
+        }
+
+        knights.is_empty()
+            && ((bishops & Bitboard::DARK_SQUARES).is_empty()
+                || (bishops & Bitboard::LIGHT_SQUARES).is_empty())
+    }
+
+    /// Returns true if fifty moves by each side have passed without a capture or pawn
+    /// move, so either player may claim a draw
+    pub fn is_fifty_move_rule(&self) -> bool {
+        self.halfmove_clock() >= 100
+    }
+
+    /// Returns true if seventy-five moves by each side have passed without a capture or
+    /// pawn move, which ends the game in a draw unless the last move gave checkmate
+    pub fn is_seventy_five_move_rule(&self) -> bool {
+        self.halfmove_clock() >= 150
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insufficient_material() {
+        for fen in [
+            "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+            "8/8/4k3/8/8/3KN3/8/8 w - - 0 1",
+            "8/8/4kb2/8/8/3K4/8/8 w - - 0 1",
+            "8/8/4kb2/8/8/3KB3/8/8 w - - 0 1",
+            "8/1b6/4k3/8/8/3K4/6B1/5B2 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(board.is_insufficient_material(), "{}", fen);
+        }
+
+        for fen in [
+            "8/8/4k3/8/8/3K4/4P3/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/4R3/8 w - - 0 1",
+            "8/8/4k3/8/8/3KNN2/8/8 w - - 0 1",
+            "8/8/4kn2/8/8/3KN3/8/8 w - - 0 1",
+            "8/8/4kb2/8/8/3K1B2/8/8 w - - 0 1",
+            "8/8/4k3/8/8/3KBN2/8/8 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(!board.is_insufficient_material(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_move_rules() {
+        let board = Board::from_fen("8/8/4k3/8/8/3K4/4R3/8 w - - 99 80").unwrap();
+        assert!(!board.is_fifty_move_rule());
+
+        let board = Board::from_fen("8/8/4k3/8/8/3K4/4R3/8 w - - 100 80").unwrap();
+        assert!(board.is_fifty_move_rule());
+        assert!(!board.is_seventy_five_move_rule());
+
+        let board = Board::from_fen("8/8/4k3/8/8/3K4/4R3/8 w - - 150 100").unwrap();
+        assert!(board.is_seventy_five_move_rule());
+    }
+}
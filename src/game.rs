@@ -0,0 +1,513 @@
+use crate::board::{Board, ParseFenError, Undo};
+use crate::moves::Move;
+use crate::san::ParseSanError;
+use crate::types::{GameResult, PieceColor};
+use crate::uci::ParseUciError;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+};
+
+/// The reason a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    Timeout,
+    Agreement,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        let text = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::Resignation => "resignation",
+            Termination::Timeout => "timeout",
+            Termination::Agreement => "draw by agreement",
+            Termination::ThreefoldRepetition => "threefold repetition",
+            Termination::FivefoldRepetition => "fivefold repetition",
+            Termination::FiftyMoveRule => "fifty-move rule",
+            Termination::SeventyFiveMoveRule => "seventy-five-move rule",
+            Termination::InsufficientMaterial => "insufficient material",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Error returned when an action cannot be applied to a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// The game already has a result
+    GameOver,
+    /// The move is not legal in the current position
+    IllegalMove,
+    /// A draw was claimed without a repetition or the fifty-move rule applying
+    NoDrawToClaim,
+    /// The SAN string could not be matched to a legal move
+    InvalidSan(ParseSanError),
+    /// The UCI string could not be matched to a legal move
+    InvalidUci(ParseUciError),
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            GameError::GameOver => write!(f, "Game error: the game is over"),
+            GameError::IllegalMove => write!(f, "Game error: illegal move"),
+            GameError::NoDrawToClaim => write!(f, "Game error: no draw to claim"),
+            GameError::InvalidSan(e) => write!(f, "Game error: {}", e),
+            GameError::InvalidUci(e) => write!(f, "Game error: {}", e),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+/// A move played in a game together with what is needed to take it back
+#[derive(Debug, Clone)]
+struct PlayedMove {
+    san: String,
+    undo: Undo,
+}
+
+/// A game of chess: a starting position, the moves played from it and the result
+/// Moves taken back with `undo` can be replayed with `redo` until a different move is
+/// played. Undoing a move also clears any result, including a resignation or timeout.
+#[derive(Debug, Clone)]
+pub struct Game {
+    starting_board: Board,
+    board: Board,
+    history: Vec<PlayedMove>,
+    redo: Vec<Move>,
+    result: GameResult,
+    termination: Option<Termination>,
+}
+
+impl Game {
+    /// Creates a game from the standard starting position
+    pub fn new() -> Game {
+        Game::from_board(Board::startpos())
+    }
+
+    /// Creates a game starting from the given position
+    pub fn from_board(board: Board) -> Game {
+        let mut game = Game {
+            starting_board: board.clone(),
+            board,
+            history: Vec::new(),
+            redo: Vec::new(),
+            result: GameResult::Ongoing,
+            termination: None,
+        };
+        game.update_result();
+        game
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Game, ParseFenError> {
+        Ok(Game::from_board(Board::from_fen(fen)?))
+    }
+
+    /// Returns the current position
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns the position the game started from
+    pub fn starting_board(&self) -> &Board {
+        &self.starting_board
+    }
+
+    /// Returns the moves played so far
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.history.iter().map(|played| played.undo.mv())
+    }
+
+    /// Returns the moves played so far in Standard Algebraic Notation
+    pub fn san_moves(&self) -> impl Iterator<Item = &str> + '_ {
+        self.history.iter().map(|played| played.san.as_str())
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    /// Returns why the game ended, or None while it is ongoing
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result != GameResult::Ongoing
+    }
+
+    /// Plays a legal move, discarding any moves that could be redone
+    pub fn play(&mut self, mv: Move) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        if !self.board.legal_moves().contains(&mv) {
+            return Err(GameError::IllegalMove);
+        }
+        self.push_move(mv);
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Plays a move given in Standard Algebraic Notation
+    pub fn play_san(&mut self, san: &str) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        let mv = self.board.parse_san(san).map_err(GameError::InvalidSan)?;
+        self.play(mv)
+    }
+
+    /// Plays a move given in UCI long algebraic notation
+    pub fn play_uci(&mut self, uci: &str) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        let mv = self.board.parse_uci(uci).map_err(GameError::InvalidUci)?;
+        self.play(mv)
+    }
+
+    /// Takes back the last move, returning it, or None at the start of the game
+    pub fn undo(&mut self) -> Option<Move> {
+        let played = self.history.pop()?;
+        self.board.unmake_move(played.undo);
+        self.redo.push(played.undo.mv());
+        self.update_result();
+        Some(played.undo.mv())
+    }
+
+    /// Replays the last move taken back, returning it, or None if there is none
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = self.redo.pop()?;
+        self.push_move(mv);
+        Some(mv)
+    }
+
+    /// Ends the game with `color` resigning
+    pub fn resign(&mut self, color: PieceColor) -> Result<(), GameError> {
+        self.end(win_for(color.opposite()), Termination::Resignation)
+    }
+
+    /// Ends the game with `color` running out of time
+    pub fn timeout(&mut self, color: PieceColor) -> Result<(), GameError> {
+        self.end(win_for(color.opposite()), Termination::Timeout)
+    }
+
+    /// Returns how many times the current position has occurred in the game
+    /// Positions are compared by Zobrist key, so they match when the pieces, side to
+    /// move, castling rights and possible en passant captures are the same. Only the
+    /// moves since the last capture or pawn move can repeat the position.
+    pub fn repetition_count(&self) -> usize {
+        let key = self.board.zobrist_key();
+        let reversible = self.board.halfmove_clock() as usize;
+        1 + self
+            .history
+            .iter()
+            .rev()
+            .take(reversible)
+            .skip(1)
+            .step_by(2)
+            .filter(|played| played.undo.zobrist_key() == key)
+            .count()
+    }
+
+    /// Returns true if the current position has occurred at least three times
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// Returns true if the current position has occurred at least five times
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
+    }
+
+    /// Returns true if the player to move may claim a draw by threefold repetition or
+    /// the fifty-move rule
+    pub fn can_claim_draw(&self) -> bool {
+        !self.is_over() && (self.is_threefold_repetition() || self.board.is_fifty_move_rule())
+    }
+
+    /// Ends the game in a draw by threefold repetition or the fifty-move rule
+    pub fn claim_draw(&mut self) -> Result<(), GameError> {
+        let termination = if self.is_threefold_repetition() {
+            Termination::ThreefoldRepetition
+        } else if self.board.is_fifty_move_rule() {
+            Termination::FiftyMoveRule
+        } else if self.is_over() {
+            return Err(GameError::GameOver);
+        } else {
+            return Err(GameError::NoDrawToClaim);
+        };
+        self.end(GameResult::Draw, termination)
+    }
+
+    /// Ends the game as a draw agreed by both players
+    pub fn agree_draw(&mut self) -> Result<(), GameError> {
+        self.end(GameResult::Draw, Termination::Agreement)
+    }
+
+    fn end(&mut self, result: GameResult, termination: Termination) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        self.result = result;
+        self.termination = Some(termination);
+        // Moves taken back before the game ended can no longer be replayed
+        self.redo.clear();
+        Ok(())
+    }
+
+    fn push_move(&mut self, mv: Move) {
+        let san = self.board.san(mv);
+        let undo = self.board.make_move(mv);
+        self.history.push(PlayedMove { san, undo });
+        self.update_result();
+    }
+
+    /// Sets the result from the rules that end the game without a claim
+    fn update_result(&mut self) {
+        let draw = if self.board.is_checkmate() {
+            self.result = win_for(self.board.side_to_move().opposite());
+            self.termination = Some(Termination::Checkmate);
+            return;
+        } else if self.board.is_stalemate() {
+            Some(Termination::Stalemate)
+        } else if self.board.is_insufficient_material() {
+            Some(Termination::InsufficientMaterial)
+        } else if self.board.is_seventy_five_move_rule() {
+            Some(Termination::SeventyFiveMoveRule)
+        } else if self.is_fivefold_repetition() {
+            Some(Termination::FivefoldRepetition)
+        } else {
+            None
+        };
+
+        self.result = match draw {
+            Some(_) => GameResult::Draw,
+            None => GameResult::Ongoing,
+        };
+        self.termination = draw;
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn win_for(color: PieceColor) -> GameResult {
+    match color {
+        PieceColor::White => GameResult::WhiteWins,
+        PieceColor::Black => GameResult::BlackWins,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_records_moves() {
+        let mut game = Game::new();
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bb5"] {
+            game.play_san(san).unwrap();
+        }
+        game.play_uci("a7a6").unwrap();
+        assert_eq!(
+            game.san_moves().collect::<Vec<_>>(),
+            vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]
+        );
+        assert_eq!(game.moves().count(), 6);
+        assert_eq!(
+            game.board().to_fen(),
+            "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4"
+        );
+        assert_eq!(game.starting_board(), &Board::startpos());
+
+        assert_eq!(
+            game.play_san("Ke3"),
+            Err(GameError::InvalidSan(ParseSanError::IllegalMove))
+        );
+        let illegal = Board::startpos().parse_uci("e2e4").unwrap();
+        assert_eq!(game.play(illegal), Err(GameError::IllegalMove));
+    }
+
+    #[test]
+    fn test_game_undo_redo() {
+        let mut game = Game::new();
+        game.play_san("d4").unwrap();
+        game.play_san("d5").unwrap();
+        let after_two = game.board().clone();
+
+        let d5 = game.undo().unwrap();
+        assert_eq!(game.board().san(d5), "d5");
+        assert_eq!(game.undo().map(|mv| mv.to_string()), Some("d2d4".into()));
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.board(), &Board::startpos());
+
+        game.redo().unwrap();
+        assert_eq!(game.redo(), Some(d5));
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.board(), &after_two);
+
+        // Playing a new move discards the moves that could be redone
+        game.undo();
+        game.play_san("Nf6").unwrap();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.san_moves().last(), Some("Nf6"));
+    }
+
+    #[test]
+    fn test_game_checkmate_and_stalemate() {
+        let mut game = Game::new();
+        for san in ["f3", "e5", "g4", "Qh4#"] {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.termination(), Some(Termination::Checkmate));
+        assert_eq!(game.play_san("a3"), Err(GameError::GameOver));
+
+        game.undo();
+        assert_eq!(game.result(), GameResult::Ongoing);
+        assert_eq!(game.termination(), None);
+
+        let mut game = Game::from_fen("7k/8/6Q1/8/8/8/8/K7 w - - 0 1").unwrap();
+        game.play_san("Qf7").unwrap();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::Stalemate));
+
+        let game = Game::from_fen("7k/5Q2/8/8/8/8/8/K7 b - - 0 1").unwrap();
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn test_game_resignation_timeout_agreement() {
+        let mut game = Game::new();
+        game.resign(PieceColor::White).unwrap();
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.termination(), Some(Termination::Resignation));
+        assert_eq!(game.agree_draw(), Err(GameError::GameOver));
+
+        let mut game = Game::new();
+        game.play_san("e4").unwrap();
+        game.timeout(PieceColor::Black).unwrap();
+        assert_eq!(game.result(), GameResult::WhiteWins);
+        assert_eq!(game.termination(), Some(Termination::Timeout));
+
+        // Taking back a move reopens the game
+        game.undo();
+        assert!(!game.is_over());
+        game.agree_draw().unwrap();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::Agreement));
+    }
+
+    #[test]
+    fn test_game_redo_after_end() {
+        let mut game = Game::new();
+        game.play_uci("e2e4").unwrap();
+        game.undo();
+        game.resign(PieceColor::White).unwrap();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.termination(), Some(Termination::Resignation));
+        assert_eq!(game.play_uci("e7e5"), Err(GameError::GameOver));
+
+        let mut game = Game::new();
+        game.play_uci("e2e4").unwrap();
+        game.undo();
+        game.agree_draw().unwrap();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::Agreement));
+        assert_eq!(game.board(), &Board::startpos());
+    }
+
+    #[test]
+    fn test_game_repetition() {
+        let mut game = Game::new();
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+        for san in shuffle {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+        assert!(!game.can_claim_draw());
+        assert_eq!(game.claim_draw(), Err(GameError::NoDrawToClaim));
+
+        for san in shuffle {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 3);
+        assert!(game.is_threefold_repetition());
+        assert!(game.can_claim_draw());
+        assert!(!game.is_over());
+
+        for san in shuffle.iter().cycle().take(8) {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 5);
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::FivefoldRepetition));
+
+        game.undo();
+        assert!(!game.is_over());
+        game.claim_draw().unwrap();
+        assert_eq!(game.termination(), Some(Termination::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn test_game_repetition_needs_same_rights() {
+        // The first Ke1 loses the castling rights, so the starting position never recurs
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        for san in ["Kd1", "Kd8", "Ke1", "Ke8"].iter().cycle().take(8) {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+
+        // Pawn moves are irreversible, so earlier positions are not counted
+        let mut game = Game::new();
+        for san in [
+            "Nf3", "Nf6", "Ng1", "Ng8", "e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8",
+        ] {
+            game.play_san(san).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+    }
+
+    #[test]
+    fn test_game_move_rules_and_material() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4R3/4K3 w - - 99 80").unwrap();
+        assert!(!game.can_claim_draw());
+        game.play_san("Rd2").unwrap();
+        assert!(game.can_claim_draw());
+        game.claim_draw().unwrap();
+        assert_eq!(game.termination(), Some(Termination::FiftyMoveRule));
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4R3/4K3 w - - 149 100").unwrap();
+        game.play_san("Rd2").unwrap();
+        assert_eq!(game.termination(), Some(Termination::SeventyFiveMoveRule));
+
+        // Checkmate on the last move takes precedence over the seventy-five-move rule
+        let mut game = Game::from_fen("k7/8/1K6/8/8/8/8/7R w - - 149 100").unwrap();
+        game.play_san("Rh8").unwrap();
+        assert_eq!(game.termination(), Some(Termination::Checkmate));
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+        game.play_san("Kxd2").unwrap();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::InsufficientMaterial));
+    }
+}
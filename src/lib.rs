@@ -1,5 +1,35 @@
+pub mod attacks;
+mod bitboard;
 mod board;
+mod castling;
+mod draw;
+mod game;
+mod movegen;
+mod moves;
+mod perft;
+mod pgn;
+mod polyglot;
+mod san;
+mod search;
+mod see;
 mod types;
+mod uci;
+mod validate;
+mod zobrist;
 
-pub use crate::board::Board;
-pub use crate::types::{ParseSquareError, Piece, PieceColor, PieceKind, Square};
+pub use crate::bitboard::{Bitboard, BitboardIter};
+pub use crate::board::{Board, EnPassantMode, ParseFenError, ParseFenErrorKind, Undo};
+pub use crate::castling::{CastlingRights, CastlingSide, ParseCastlingRightsError};
+pub use crate::game::{Game, GameError, Termination};
+pub use crate::moves::{Move, MoveFlags};
+pub use crate::pgn::{PgnError, PgnErrorKind, PgnEval, PgnGame, PgnMove, PgnReader, PgnWriter};
+pub use crate::san::ParseSanError;
+pub use crate::search::{
+    Bound, MATE_SCORE, SearchLimits, SearchParams, SearchResult, Searcher, TranspositionTable,
+    TtEntry, evaluate, is_mate_score, piece_value,
+};
+pub use crate::types::{
+    GameResult, ParseGameResultError, ParseSquareError, Piece, PieceColor, PieceKind, Square,
+};
+pub use crate::uci::ParseUciError;
+pub use crate::validate::PositionProblem;
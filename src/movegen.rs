@@ -0,0 +1,579 @@
+use crate::attacks::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks,
+};
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::moves::{Move, MoveFlags};
+use crate::types::{PieceColor, PieceKind, Square};
+
+const PROMOTION_KINDS: [PieceKind; 4] = [
+    PieceKind::Queen,
+    PieceKind::Rook,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+];
+
+/// Which moves `generate_moves` adds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MoveKinds {
+    /// Captures and promotions
+    Noisy,
+    /// Every other move
+    Quiet,
+    All,
+}
+
+impl MoveKinds {
+    fn includes(self, noisy: bool) -> bool {
+        match self {
+            MoveKinds::Noisy => noisy,
+            MoveKinds::Quiet => !noisy,
+            MoveKinds::All => true,
+        }
+    }
+}
+
+impl Board {
+    /// Returns all legal moves for the side to move
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut board = self.clone();
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|&mv| board.keeps_king_safe(mv))
+            .collect()
+    }
+
+    /// Returns the en passant square only if a legal en passant capture onto it exists
+    /// `en_passant_square` is set after every double push, this is the stricter
+    /// convention used by Polyglot books and most online databases.
+    pub fn legal_en_passant_square(&self) -> Option<Square> {
+        let to = self.en_passant_square()?;
+        let color = self.side_to_move();
+        // The pawn that just moved stands one rank behind the target, seen from the
+        // side to move
+        let behind = match color {
+            PieceColor::White => -1,
+            PieceColor::Black => 1,
+        };
+        let victim = to.offset(behind, 0)?;
+        if !self
+            .pieces(color.opposite(), PieceKind::Pawn)
+            .contains(victim)
+        {
+            return None;
+        }
+
+        let mut board = self.clone();
+        let attackers = pawn_attacks(color.opposite(), to) & self.pieces(color, PieceKind::Pawn);
+        attackers
+            .into_iter()
+            .any(|from| {
+                let flags = MoveFlags::CAPTURE | MoveFlags::EN_PASSANT;
+                board.keeps_king_safe(Move::new(from, to, None, flags))
+            })
+            .then_some(to)
+    }
+
+    /// Returns true if the pseudo-legal move does not leave the mover's king in check
+    /// The move is played and taken back, leaving the board unchanged.
+    pub(crate) fn keeps_king_safe(&mut self, mv: Move) -> bool {
+        let color = self.side_to_move();
+        let undo = self.make_move(mv);
+        let legal = self
+            .king_square(color)
+            .is_none_or(|king| !self.is_square_attacked(king, color.opposite()));
+        self.unmake_move(undo);
+        legal
+    }
+
+    /// Returns all moves for the side to move that follow the movement rules of each piece,
+    /// without checking whether they leave the own king in check
+    /// Castling moves are only generated when the king does not pass through or land on an
+    /// attacked square, as that cannot be detected after the move is played.
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.generate_moves(MoveKinds::All, Bitboard::FULL, &mut moves);
+        moves
+    }
+
+    /// Returns the pseudo-legal captures and promotions, including en passant captures and
+    /// promotions without a capture
+    pub fn pseudo_legal_captures(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.generate_moves(MoveKinds::Noisy, Bitboard::FULL, &mut moves);
+        moves
+    }
+
+    /// Returns the pseudo-legal moves that are neither captures nor promotions
+    pub fn pseudo_legal_quiets(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.generate_moves(MoveKinds::Quiet, Bitboard::FULL, &mut moves);
+        moves
+    }
+
+    /// Returns true if the move is one of `pseudo_legal_moves`
+    /// Only the moves of the piece on the origin square are generated, which makes this a
+    /// cheap way to check a move from another position, e.g. a stored best move.
+    pub fn is_pseudo_legal(&self, mv: Move) -> bool {
+        let mut moves = Vec::new();
+        self.generate_moves(MoveKinds::All, Bitboard::from_square(mv.from()), &mut moves);
+        moves.contains(&mv)
+    }
+
+    /// Returns true if the move is one of `legal_moves`
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.is_pseudo_legal(mv) && self.clone().keeps_king_safe(mv)
+    }
+
+    /// Adds the pseudo-legal moves of the given kinds by the pieces on `from` squares
+    fn generate_moves(&self, kinds: MoveKinds, from: Bitboard, moves: &mut Vec<Move>) {
+        let color = self.side_to_move();
+        let occupied = self.occupied();
+        let targets = match kinds {
+            MoveKinds::Noisy => self.by_color(color.opposite()),
+            MoveKinds::Quiet => !occupied,
+            MoveKinds::All => !self.by_color(color),
+        };
+
+        self.pawn_moves(color, kinds, from, moves);
+
+        for from in self.pieces(color, PieceKind::Knight) & from {
+            self.push_moves(from, knight_attacks(from) & targets, moves);
+        }
+
+        for from in self.pieces(color, PieceKind::Bishop) & from {
+            self.push_moves(from, bishop_attacks(from, occupied) & targets, moves);
+        }
+
+        for from in self.pieces(color, PieceKind::Rook) & from {
+            self.push_moves(from, rook_attacks(from, occupied) & targets, moves);
+        }
+
+        for from in self.pieces(color, PieceKind::Queen) & from {
+            self.push_moves(from, queen_attacks(from, occupied) & targets, moves);
+        }
+
+        for from in self.pieces(color, PieceKind::King) & from {
+            self.push_moves(from, king_attacks(from) & targets, moves);
+            if kinds.includes(false) {
+                self.castling_moves(from, color, moves);
+            }
+        }
+    }
+
+    /// Returns true if any piece of color `by` attacks the given square
+    pub fn is_square_attacked(&self, square: Square, by: PieceColor) -> bool {
+        !self.attackers(square, by).is_empty()
+    }
+
+    /// Returns the squares of all pieces of color `by` that attack the given square
+    pub fn attackers(&self, square: Square, by: PieceColor) -> Bitboard {
+        let occupied = self.occupied();
+        let queens = self.by_kind(PieceKind::Queen);
+
+        // A pawn of color `by` attacks this square exactly when a pawn of the other
+        // color standing here would attack the pawn's square
+        let attackers = (pawn_attacks(by.opposite(), square) & self.by_kind(PieceKind::Pawn))
+            | (knight_attacks(square) & self.by_kind(PieceKind::Knight))
+            | (king_attacks(square) & self.by_kind(PieceKind::King))
+            | (bishop_attacks(square, occupied) & (self.by_kind(PieceKind::Bishop) | queens))
+            | (rook_attacks(square, occupied) & (self.by_kind(PieceKind::Rook) | queens));
+
+        attackers & self.by_color(by)
+    }
+
+    /// Returns true if the king of the side to move is attacked
+    pub fn in_check(&self) -> bool {
+        !self.checkers().is_empty()
+    }
+
+    /// Returns the squares of all pieces giving check to the king of the side to move
+    pub fn checkers(&self) -> Bitboard {
+        let color = self.side_to_move();
+        self.king_square(color).map_or(Bitboard::EMPTY, |king| {
+            self.attackers(king, color.opposite())
+        })
+    }
+
+    /// Returns true if the side to move is in check and has no legal moves
+    pub fn is_checkmate(&self) -> bool {
+        self.in_check() && self.legal_moves().is_empty()
+    }
+
+    /// Returns true if the side to move is not in check but has no legal moves
+    pub fn is_stalemate(&self) -> bool {
+        !self.in_check() && self.legal_moves().is_empty()
+    }
+
+    /// Adds a move from `from` to every square in `targets`, flagging captures
+    fn push_moves(&self, from: Square, targets: Bitboard, moves: &mut Vec<Move>) {
+        let enemies = self.by_color(self.side_to_move().opposite());
+        for to in targets {
+            let flags = if enemies.contains(to) {
+                MoveFlags::CAPTURE
+            } else {
+                MoveFlags::QUIET
+            };
+            moves.push(Move::new(from, to, None, flags));
+        }
+    }
+
+    fn pawn_moves(
+        &self,
+        color: PieceColor,
+        kinds: MoveKinds,
+        from: Bitboard,
+        moves: &mut Vec<Move>,
+    ) {
+        let (direction, start_rank, promotion_rank) = match color {
+            PieceColor::White => (1, 1, 7),
+            PieceColor::Black => (-1, 6, 0),
+        };
+        let empty = !self.occupied();
+        let enemies = self.by_color(color.opposite());
+
+        let mut push = |from: Square, to: Square, flags: MoveFlags| {
+            let promotion = to.rank() == promotion_rank;
+            if !kinds.includes(promotion || flags.contains(MoveFlags::CAPTURE)) {
+                return;
+            }
+            if promotion {
+                for kind in PROMOTION_KINDS {
+                    moves.push(Move::new(from, to, Some(kind), flags));
+                }
+            } else {
+                moves.push(Move::new(from, to, None, flags));
+            }
+        };
+
+        for from in self.pieces(color, PieceKind::Pawn) & from {
+            if let Some(to) = from.offset(direction, 0)
+                && empty.contains(to)
+            {
+                push(from, to, MoveFlags::QUIET);
+
+                if from.rank() == start_rank
+                    && let Some(to) = to.offset(direction, 0)
+                    && empty.contains(to)
+                {
+                    push(from, to, MoveFlags::DOUBLE_PUSH);
+                }
+            }
+
+            let attacks = pawn_attacks(color, from);
+            for to in attacks & enemies {
+                push(from, to, MoveFlags::CAPTURE);
+            }
+
+            if let Some(to) = self.en_passant_square()
+                && attacks.contains(to)
+            {
+                // Only capture en passant if the pawn that just moved is actually there
+                let victim = Square::from_rank_and_file(from.rank(), to.file()).unwrap();
+                if self
+                    .pieces(color.opposite(), PieceKind::Pawn)
+                    .contains(victim)
+                {
+                    push(from, to, MoveFlags::CAPTURE | MoveFlags::EN_PASSANT);
+                }
+            }
+        }
+    }
+
+    fn castling_moves(&self, from: Square, color: PieceColor, moves: &mut Vec<Move>) {
+        let rank = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => 7,
+        };
+        if from != Square::from_rank_and_file(rank, 4).unwrap() {
+            return;
+        }
+
+        let rooks = self.pieces(color, PieceKind::Rook);
+        let occupied = self.occupied();
+        let square = |file| Square::from_rank_and_file(rank, file).unwrap();
+        let empty = |files: &[u8]| files.iter().all(|&f| !occupied.contains(square(f)));
+        let safe = |files: &[u8]| {
+            files
+                .iter()
+                .all(|&f| !self.is_square_attacked(square(f), color.opposite()))
+        };
+
+        if self.can_castle_kingside(color)
+            && rooks.contains(square(7))
+            && empty(&[5, 6])
+            && safe(&[4, 5, 6])
+        {
+            moves.push(Move::new(from, square(6), None, MoveFlags::CASTLE));
+        }
+
+        if self.can_castle_queenside(color)
+            && rooks.contains(square(0))
+            && empty(&[1, 2, 3])
+            && safe(&[4, 3, 2])
+        {
+            moves.push(Move::new(from, square(2), None, MoveFlags::CASTLE));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(s: &str) -> Square {
+        s.parse().unwrap()
+    }
+
+    fn has_move(moves: &[Move], uci: &str) -> bool {
+        moves.iter().any(|mv| mv.to_string() == uci)
+    }
+
+    #[test]
+    fn test_startpos_moves() {
+        let board = Board::startpos();
+        let moves = board.legal_moves();
+        assert_eq!(moves.len(), 20);
+        assert!(has_move(&moves, "e2e4"));
+        assert!(has_move(&moves, "g1f3"));
+        assert!(
+            moves
+                .iter()
+                .find(|mv| mv.to_string() == "e2e4")
+                .unwrap()
+                .is_double_push()
+        );
+    }
+
+    #[test]
+    fn test_legal_move_counts() {
+        for (fen, count) in [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                48,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 14),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                6,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                44,
+            ),
+            (
+                "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+                46,
+            ),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.legal_moves().len(), count, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_castling_moves() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = board.legal_moves();
+        let castles: Vec<_> = moves.iter().filter(|mv| mv.is_castle()).collect();
+        assert_eq!(castles.len(), 2);
+        assert!(has_move(&moves, "e1g1"));
+        assert!(has_move(&moves, "e1c1"));
+
+        // Without rights castling is not possible
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        assert!(!board.legal_moves().iter().any(|mv| mv.is_castle()));
+
+        // The king may not pass through an attacked square
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQ - 0 1").unwrap();
+        let moves = board.legal_moves();
+        assert!(!has_move(&moves, "e1g1"));
+        assert!(has_move(&moves, "e1c1"));
+
+        // Castling out of check is not allowed
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/4r3/R3K2R w KQ - 0 1").unwrap();
+        assert!(!board.legal_moves().iter().any(|mv| mv.is_castle()));
+
+        // The b-file square only needs to be empty, not safe
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/1r6/R3K2R w Q - 0 1").unwrap();
+        assert!(has_move(&board.legal_moves(), "e1c1"));
+    }
+
+    #[test]
+    fn test_en_passant_moves() {
+        let board = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 2").unwrap();
+        // Capturing en passant would expose the king along the rank
+        assert!(!has_move(&board.legal_moves(), "b5c6"));
+        assert!(has_move(&board.pseudo_legal_moves(), "b5c6"));
+
+        let board =
+            Board::from_fen("rnbqkb1r/ppppp1pp/7n/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let mv = *board
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_string() == "e5f6")
+            .unwrap();
+        assert!(mv.is_en_passant());
+        assert!(mv.is_capture());
+    }
+
+    #[test]
+    fn test_legal_en_passant_square() {
+        // Pinned along the rank, so the capture is not legal
+        let board = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 2").unwrap();
+        assert_eq!(board.en_passant_square(), Some("c6".parse().unwrap()));
+        assert_eq!(board.legal_en_passant_square(), None);
+
+        // No pawn can capture at all
+        let mut board = Board::startpos();
+        board.make_move(
+            *board
+                .legal_moves()
+                .iter()
+                .find(|mv| mv.to_string() == "e2e4")
+                .unwrap(),
+        );
+        assert_eq!(board.en_passant_square(), Some("e3".parse().unwrap()));
+        assert_eq!(board.legal_en_passant_square(), None);
+
+        let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(board.legal_en_passant_square(), Some("e3".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_promotion_moves() {
+        let board = Board::from_fen("1n5k/P7/8/8/8/8/8/7K w - - 0 1").unwrap();
+        let moves = board.legal_moves();
+        let promotions: Vec<_> = moves.iter().filter(|mv| mv.is_promotion()).collect();
+        // Four pushes to a8 and four captures on b8
+        assert_eq!(promotions.len(), 8);
+        assert!(has_move(&moves, "a7a8q"));
+        assert!(has_move(&moves, "a7b8n"));
+    }
+
+    #[test]
+    fn test_pinned_piece_moves() {
+        // The knight on e2 is pinned against the king by the rook on e8
+        let board = Board::from_fen("4r2k/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        assert!(
+            !board
+                .legal_moves()
+                .iter()
+                .any(|mv| mv.from() == square("e2"))
+        );
+    }
+
+    #[test]
+    fn test_checkmated_has_no_moves() {
+        let board =
+            Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        assert!(board.legal_moves().is_empty());
+        assert!(board.is_square_attacked(square("e1"), PieceColor::Black));
+    }
+
+    #[test]
+    fn test_is_square_attacked() {
+        let board = Board::from_fen("4k3/8/8/3p4/8/1N6/8/R3K3 w - - 0 1").unwrap();
+        // Pawns attack diagonally forward only
+        assert!(board.is_square_attacked(square("c4"), PieceColor::Black));
+        assert!(board.is_square_attacked(square("e4"), PieceColor::Black));
+        assert!(!board.is_square_attacked(square("d4"), PieceColor::Black));
+        // Knight, king and rook, with the rook blocked by its own king
+        assert!(board.is_square_attacked(square("d4"), PieceColor::White));
+        assert!(board.is_square_attacked(square("f2"), PieceColor::White));
+        assert!(board.is_square_attacked(square("a8"), PieceColor::White));
+        assert!(!board.is_square_attacked(square("g1"), PieceColor::White));
+        assert!(!board.is_square_attacked(square("h8"), PieceColor::White));
+    }
+
+    #[test]
+    fn test_in_check_and_checkers() {
+        assert!(!Board::startpos().in_check());
+        assert!(Board::startpos().checkers().is_empty());
+
+        // Double check from a knight and a rook
+        let board = Board::from_fen("4r2k/8/8/8/8/3n4/8/4K3 w - - 0 1").unwrap();
+        assert!(board.in_check());
+        let checkers: Vec<_> = board.checkers().into_iter().collect();
+        assert_eq!(checkers, vec![square("d3"), square("e8")]);
+        // Only king moves can answer a double check
+        assert!(
+            board
+                .legal_moves()
+                .iter()
+                .all(|mv| mv.from() == square("e1"))
+        );
+    }
+
+    #[test]
+    fn test_checkmate_and_stalemate() {
+        let mate = Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap();
+        assert!(mate.is_checkmate());
+        assert!(!mate.is_stalemate());
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(stalemate.is_stalemate());
+        assert!(!stalemate.is_checkmate());
+
+        let startpos = Board::startpos();
+        assert!(!startpos.is_checkmate());
+        assert!(!startpos.is_stalemate());
+    }
+
+    #[test]
+    fn test_captures_and_quiets_split_moves() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/8/8/2p1pP2/8/8/8/4K3 w - e6 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let captures = board.pseudo_legal_captures();
+            let quiets = board.pseudo_legal_quiets();
+            assert!(
+                captures
+                    .iter()
+                    .all(|mv| mv.is_capture() || mv.is_promotion())
+            );
+            assert!(
+                quiets
+                    .iter()
+                    .all(|mv| !mv.is_capture() && !mv.is_promotion())
+            );
+
+            let mut split: Vec<_> = captures.into_iter().chain(quiets).collect();
+            let mut all = board.pseudo_legal_moves();
+            split.sort_by_key(|mv| mv.to_string());
+            all.sort_by_key(|mv| mv.to_string());
+            assert_eq!(split, all, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_is_pseudo_legal_and_is_legal() {
+        let board = Board::from_fen("4r2k/8/8/8/8/8/4N3/R3K3 w Q - 0 1").unwrap();
+        for mv in board.pseudo_legal_moves() {
+            assert!(board.is_pseudo_legal(mv));
+            assert_eq!(board.is_legal(mv), board.legal_moves().contains(&mv));
+        }
+
+        // The pinned knight's moves are pseudo-legal only
+        let pinned = Move::new(square("e2"), square("c3"), None, MoveFlags::QUIET);
+        assert!(board.is_pseudo_legal(pinned));
+        assert!(!board.is_legal(pinned));
+
+        // Moves from other positions are rejected
+        let startpos = Board::startpos();
+        assert!(!board.is_pseudo_legal(startpos.parse_uci("e2e4").unwrap()));
+        assert!(!board.is_pseudo_legal(startpos.parse_uci("g1f3").unwrap()));
+        let castle = board.parse_uci("e1c1").unwrap();
+        assert!(
+            !Board::from_fen("4r2k/8/8/8/8/8/4N3/R3K3 w - - 0 1")
+                .unwrap()
+                .is_pseudo_legal(castle)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::types::{PieceKind, Square};
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    ops::{BitOr, BitOrAssign},
+};
+
+/// Set of flags describing the special properties of a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MoveFlags(u8);
+
+impl MoveFlags {
+    /// A move that captures nothing and is not special in any way
+    pub const QUIET: MoveFlags = MoveFlags(0);
+    /// The move captures a piece, including en passant captures
+    pub const CAPTURE: MoveFlags = MoveFlags(1);
+    /// A pawn moving two squares from its starting rank
+    pub const DOUBLE_PUSH: MoveFlags = MoveFlags(1 << 1);
+    /// A pawn capturing en passant
+    pub const EN_PASSANT: MoveFlags = MoveFlags(1 << 2);
+    /// The king castling, encoded as the king moving two squares
+    pub const CASTLE: MoveFlags = MoveFlags(1 << 3);
+
+    /// Returns true if all flags in `other` are set
+    pub fn contains(self, other: MoveFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for MoveFlags {
+    type Output = MoveFlags;
+
+    fn bitor(self, rhs: MoveFlags) -> MoveFlags {
+        MoveFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MoveFlags {
+    fn bitor_assign(&mut self, rhs: MoveFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A move from one square to another
+/// Castling is represented as the king moving two squares towards the rook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    from: Square,
+    to: Square,
+    promotion: Option<PieceKind>,
+    flags: MoveFlags,
+}
+
+impl Move {
+    /// Creates a new Move
+    pub fn new(from: Square, to: Square, promotion: Option<PieceKind>, flags: MoveFlags) -> Move {
+        Move {
+            from,
+            to,
+            promotion,
+            flags,
+        }
+    }
+
+    pub fn from(self) -> Square {
+        self.from
+    }
+
+    pub fn to(self) -> Square {
+        self.to
+    }
+
+    /// Returns the piece kind a pawn promotes to, if this is a promotion
+    pub fn promotion(self) -> Option<PieceKind> {
+        self.promotion
+    }
+
+    pub fn flags(self) -> MoveFlags {
+        self.flags
+    }
+
+    /// Returns true if the move captures a piece, including en passant
+    pub fn is_capture(self) -> bool {
+        self.flags.contains(MoveFlags::CAPTURE)
+    }
+
+    pub fn is_promotion(self) -> bool {
+        self.promotion.is_some()
+    }
+
+    pub fn is_double_push(self) -> bool {
+        self.flags.contains(MoveFlags::DOUBLE_PUSH)
+    }
+
+    pub fn is_en_passant(self) -> bool {
+        self.flags.contains(MoveFlags::EN_PASSANT)
+    }
+
+    pub fn is_castle(self) -> bool {
+        self.flags.contains(MoveFlags::CASTLE)
+    }
+}
+
+impl Display for Move {
+    /// Formats the move in long algebraic notation, e.g. `e2e4` or `e7e8q`
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}{}", self.from, self.to)?;
+        if let Some(kind) = self.promotion {
+            let c = match kind {
+                PieceKind::Queen => 'q',
+                PieceKind::Rook => 'r',
+                PieceKind::Bishop => 'b',
+                PieceKind::Knight => 'n',
+                PieceKind::King => 'k',
+                PieceKind::Pawn => 'p',
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_flags() {
+        let flags = MoveFlags::CAPTURE | MoveFlags::EN_PASSANT;
+        assert!(flags.contains(MoveFlags::CAPTURE));
+        assert!(flags.contains(MoveFlags::EN_PASSANT));
+        assert!(!flags.contains(MoveFlags::CASTLE));
+        assert!(flags.contains(MoveFlags::QUIET));
+    }
+
+    #[test]
+    fn test_move_accessors() {
+        let mv = Move::new(
+            "e7".parse().unwrap(),
+            "d8".parse().unwrap(),
+            Some(PieceKind::Queen),
+            MoveFlags::CAPTURE,
+        );
+        assert_eq!(mv.from(), "e7".parse().unwrap());
+        assert_eq!(mv.to(), "d8".parse().unwrap());
+        assert_eq!(mv.promotion(), Some(PieceKind::Queen));
+        assert!(mv.is_capture());
+        assert!(mv.is_promotion());
+        assert!(!mv.is_castle());
+        assert!(!mv.is_en_passant());
+        assert!(!mv.is_double_push());
+    }
+
+    #[test]
+    fn test_move_display() {
+        let mv = Move::new(
+            "e2".parse().unwrap(),
+            "e4".parse().unwrap(),
+            None,
+            MoveFlags::DOUBLE_PUSH,
+        );
+        assert_eq!(mv.to_string(), "e2e4");
+
+        let mv = Move::new(
+            "b2".parse().unwrap(),
+            "b1".parse().unwrap(),
+            Some(PieceKind::Knight),
+            MoveFlags::QUIET,
+        );
+        assert_eq!(mv.to_string(), "b2b1n");
+    }
+}
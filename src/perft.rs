@@ -0,0 +1,140 @@
+use crate::board::Board;
+use crate::moves::Move;
+
+impl Board {
+    /// Counts the leaf nodes of the legal move tree to the given depth
+    /// Used to verify move generation against published node counts.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut board = self.clone();
+        perft_inner(&mut board, depth)
+    }
+
+    /// Runs perft to the given depth below each legal root move and returns the
+    /// node count per move, in move generation order
+    /// Comparing the output with another move generator narrows a mismatch down to a line.
+    pub fn divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        let mut board = self.clone();
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let undo = board.make_move(mv);
+                let nodes = match depth {
+                    0 | 1 => 1,
+                    _ => perft_inner(&mut board, depth - 1),
+                };
+                board.unmake_move(undo);
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
+
+fn perft_inner(board: &mut Board, depth: u32) -> u64 {
+    let moves = board.legal_moves();
+
+    // Leaf counts equal the number of legal moves, no need to play them
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let undo = board.make_move(mv);
+            let nodes = perft_inner(board, depth - 1);
+            board.unmake_move(undo);
+            nodes
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the published node counts for a position, starting at depth 1
+    fn check_perft(fen: &str, counts: &[u64]) {
+        let board = Board::from_fen(fen).unwrap();
+        for (depth, &expected) in counts.iter().enumerate() {
+            let depth = depth as u32 + 1;
+            assert_eq!(board.perft(depth), expected, "{} depth {}", fen, depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_depth_zero() {
+        assert_eq!(Board::startpos().perft(0), 1);
+    }
+
+    #[test]
+    fn test_perft_startpos() {
+        check_perft(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8_902, 197_281, 4_865_609],
+        );
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        check_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2_039, 97_862, 4_085_603],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        check_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2_812, 43_238, 674_624],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        check_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9_467, 422_333],
+        );
+        // The same position with colors reversed must produce identical counts
+        check_perft(
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            &[6, 264, 9_467, 422_333],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_5() {
+        check_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1_486, 62_379, 2_103_487],
+        );
+    }
+
+    #[test]
+    fn test_perft_position_6() {
+        check_perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2_079, 89_890, 3_894_594],
+        );
+    }
+
+    #[test]
+    fn test_divide() {
+        let board = Board::startpos();
+        let divide = board.divide(3);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8_902);
+
+        let e2e4 = divide
+            .iter()
+            .find(|(mv, _)| mv.to_string() == "e2e4")
+            .unwrap();
+        assert_eq!(e2e4.1, 600);
+
+        assert!(board.divide(1).iter().all(|&(_, nodes)| nodes == 1));
+    }
+}
@@ -0,0 +1,234 @@
+mod reader;
+mod writer;
+
+pub use self::reader::{PgnError, PgnErrorKind, PgnReader};
+pub use self::writer::PgnWriter;
+
+use crate::board::{Board, ParseFenError};
+use crate::moves::Move;
+use crate::types::GameResult;
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    time::Duration,
+};
+
+/// An engine evaluation attached to a move with the `[%eval]` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnEval {
+    /// Score in hundredths of a pawn from White's point of view
+    Centipawns(i32),
+    /// Mate in the given number of moves, negative when Black mates
+    Mate(i32),
+}
+
+impl Display for PgnEval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match *self {
+            PgnEval::Centipawns(cp) => {
+                let sign = if cp < 0 { "-" } else { "" };
+                let cp = cp.unsigned_abs();
+                write!(f, "{}{}.{:02}", sign, cp / 100, cp % 100)
+            }
+            PgnEval::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// A move in a PGN game record together with its annotations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    /// The move, resolved against the position it was played in
+    pub mv: Move,
+    /// The move in Standard Algebraic Notation, as produced by `Board::san`
+    pub san: String,
+    /// Comments appearing before the move, only used for the first move of a line
+    pub comments_before: Vec<String>,
+    /// Numeric Annotation Glyphs, e.g. 1 for `!` and 2 for `?`
+    pub nags: Vec<u8>,
+    /// Comments appearing after the move
+    pub comments: Vec<String>,
+    /// Alternatives to this move, each starting from the position before it
+    pub variations: Vec<Vec<PgnMove>>,
+    /// Remaining clock time after the move, from the `[%clk]` command
+    pub clock: Option<Duration>,
+    /// Engine evaluation after the move, from the `[%eval]` command
+    pub eval: Option<PgnEval>,
+}
+
+impl PgnMove {
+    /// Creates a move without annotations
+    pub fn new(mv: Move, san: String) -> PgnMove {
+        PgnMove {
+            mv,
+            san,
+            comments_before: Vec::new(),
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+            clock: None,
+            eval: None,
+        }
+    }
+
+    /// Moves `[%clk]` and `[%eval]` commands from a comment into the move, returning the
+    /// rest of the comment
+    fn extract_commands(&mut self, comment: &str) -> String {
+        let mut text = comment.to_string();
+        let mut search_from = 0;
+
+        while let Some(start) = text[search_from..].find("[%").map(|i| i + search_from) {
+            let Some(end) = text[start..].find(']').map(|i| i + start) else {
+                break;
+            };
+            let mut args = text[start + 2..end].split_whitespace();
+            let parsed = match (args.next(), args.next()) {
+                (Some("clk"), Some(clock)) => parse_clock(clock).map(|c| self.clock = Some(c)),
+                (Some("eval"), Some(eval)) => parse_eval(eval).map(|e| self.eval = Some(e)),
+                _ => None,
+            };
+
+            if parsed.is_some() {
+                let before = text[..start].trim_end();
+                let after = text[end + 1..].trim_start();
+                let separator = if before.is_empty() || after.is_empty() {
+                    ""
+                } else {
+                    " "
+                };
+                search_from = before.len() + separator.len();
+                text = format!("{}{}{}", before, separator, after);
+            } else {
+                search_from = end + 1;
+            }
+        }
+
+        text
+    }
+}
+
+/// Parses a `[%clk]` argument of the form `h:mm:ss` with optional fractional seconds
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let mut parts = clock.rsplitn(3, ':');
+    let seconds = parts.next()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let hours: u64 = parts.next().map_or(Ok(0), str::parse).ok()?;
+
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let whole: u64 = whole.parse().ok()?;
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let millis = format!("{:0<3}", &fraction[..fraction.len().min(3)]);
+
+    Some(Duration::from_millis(
+        ((hours * 60 + minutes) * 60 + whole) * 1000 + millis.parse::<u64>().ok()?,
+    ))
+}
+
+/// Parses an `[%eval]` argument, either a pawn score like `-0.35` or a mate like `#3`,
+/// ignoring a trailing `,depth`
+fn parse_eval(eval: &str) -> Option<PgnEval> {
+    let eval = eval.split(',').next()?;
+    match eval.strip_prefix('#') {
+        Some(mate) => mate.parse().ok().map(PgnEval::Mate),
+        None => {
+            let pawns: f64 = eval.parse().ok()?;
+            pawns
+                .is_finite()
+                .then(|| PgnEval::Centipawns((pawns * 100.0).round() as i32))
+        }
+    }
+}
+
+/// A game record: the tag pairs, the main line with its variations and the result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read or added
+    pub tags: Vec<(String, String)>,
+    /// The main line of the game
+    pub moves: Vec<PgnMove>,
+    /// The result token terminating the movetext
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    /// Creates an empty game with no tags or moves
+    pub fn new() -> PgnGame {
+        PgnGame {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            result: GameResult::Ongoing,
+        }
+    }
+
+    /// Returns the value of the first tag with the given name
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a tag, replacing the value of an existing tag with the same name
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Returns the position the game starts from, taken from the `FEN` tag if present
+    pub fn starting_board(&self) -> Result<Board, ParseFenError> {
+        match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen),
+            None => Ok(Board::startpos()),
+        }
+    }
+
+    /// Returns the position at the end of the main line
+    pub fn final_board(&self) -> Result<Board, ParseFenError> {
+        let mut board = self.starting_board()?;
+        for pgn_move in &self.moves {
+            board.make_move(pgn_move.mv);
+        }
+        Ok(board)
+    }
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_commands() {
+        let board = Board::startpos();
+        let mut pgn_move = PgnMove::new(board.parse_san("e4").unwrap(), "e4".to_string());
+        let rest = pgn_move.extract_commands("[%eval -0.35] Good move [%clk 1:02:03.5] [%csl Ga4]");
+        assert_eq!(rest, "Good move [%csl Ga4]");
+        assert_eq!(pgn_move.eval, Some(PgnEval::Centipawns(-35)));
+        assert_eq!(pgn_move.clock, Some(Duration::from_millis(3_723_500)));
+
+        let rest = pgn_move.extract_commands("[%eval #-2,30] [%clk 0:05]");
+        assert_eq!(rest, "");
+        assert_eq!(pgn_move.eval, Some(PgnEval::Mate(-2)));
+        assert_eq!(pgn_move.clock, Some(Duration::from_secs(5)));
+
+        let rest = pgn_move.extract_commands("[%clk soon] [%eval x]");
+        assert_eq!(rest, "[%clk soon] [%eval x]");
+    }
+
+    #[test]
+    fn test_eval_display() {
+        assert_eq!(PgnEval::Centipawns(17).to_string(), "0.17");
+        assert_eq!(PgnEval::Centipawns(-5).to_string(), "-0.05");
+        assert_eq!(PgnEval::Centipawns(250).to_string(), "2.50");
+        assert_eq!(PgnEval::Mate(3).to_string(), "#3");
+        assert_eq!(PgnEval::Mate(-1).to_string(), "#-1");
+    }
+}
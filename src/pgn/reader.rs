@@ -0,0 +1,705 @@
+use super::{PgnGame, PgnMove};
+use crate::board::{Board, ParseFenError};
+use crate::san::ParseSanError;
+use crate::types::GameResult;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    io::{self, BufRead},
+    mem, str,
+};
+
+/// The kind of problem found while reading PGN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnErrorKind {
+    /// Reading from the underlying reader failed
+    Io(io::ErrorKind),
+    /// A tag pair is not of the form `[Name "value"]`
+    InvalidTag,
+    /// A `{` comment is not closed before the end of the input
+    UnterminatedComment,
+    /// A character that cannot start any movetext token
+    UnexpectedCharacter(char),
+    /// A `$` is not followed by a number from 0 to 255
+    InvalidNag,
+    /// The `FEN` tag does not hold a valid FEN string
+    InvalidFen(ParseFenError),
+    /// A move is not legal, ambiguous or not valid SAN in its position
+    InvalidMove(String, ParseSanError),
+    /// A `)` without matching `(`, or a game ending with variations still open
+    UnbalancedVariation,
+    /// A variation starts before any move it could be an alternative to
+    VariationWithoutMove,
+}
+
+impl Display for PgnErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            PgnErrorKind::Io(kind) => write!(f, "read failed: {}", kind),
+            PgnErrorKind::InvalidTag => write!(f, "invalid tag pair"),
+            PgnErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+            PgnErrorKind::InvalidNag => write!(f, "invalid NAG"),
+            PgnErrorKind::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error.kind()),
+            PgnErrorKind::InvalidMove(san, error) => write!(f, "{} for move '{}'", error, san),
+            PgnErrorKind::UnbalancedVariation => write!(f, "unbalanced variation"),
+            PgnErrorKind::VariationWithoutMove => write!(f, "variation before the first move"),
+        }
+    }
+}
+
+/// Error returned when a game cannot be read, with the 1-based line and column it
+/// was found at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnError {
+    kind: PgnErrorKind,
+    line: usize,
+    column: usize,
+}
+
+impl PgnError {
+    pub fn kind(&self) -> &PgnErrorKind {
+        &self.kind
+    }
+
+    /// Returns the 1-based line number the error was found on
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column, in characters, the error was found at
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(
+            f,
+            "Invalid PGN: {} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl Error for PgnError {}
+
+/// Reads the input one line at a time, tracking the current line and column
+struct Lexer<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    line: Vec<char>,
+    pos: usize,
+    line_number: usize,
+}
+
+impl<R: BufRead> Lexer<R> {
+    /// Makes sure a character is available at the current position, reading lines as
+    /// needed, and returns false at the end of the input
+    /// Lines that are not valid UTF-8 are decoded as Latin-1, the encoding of many older
+    /// PGN exports, so only genuine I/O errors are returned.
+    fn fill(&mut self) -> io::Result<bool> {
+        while self.pos >= self.line.len() {
+            self.buffer.clear();
+            if self.reader.read_until(b'\n', &mut self.buffer)? == 0 {
+                return Ok(false);
+            }
+            self.line_number += 1;
+            self.pos = 0;
+            self.line.clear();
+
+            // A `%` in the first column escapes the whole line
+            if self.buffer.starts_with(b"%") {
+                continue;
+            }
+            match str::from_utf8(&self.buffer) {
+                Ok(text) => self.line.extend(text.chars()),
+                Err(_) => self
+                    .line
+                    .extend(self.buffer.iter().map(|&byte| byte as char)),
+            }
+        }
+        Ok(true)
+    }
+
+    fn peek(&mut self) -> io::Result<Option<char>> {
+        Ok(if self.fill()? {
+            Some(self.line[self.pos])
+        } else {
+            None
+        })
+    }
+
+    fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    fn skip_line(&mut self) {
+        self.pos = self.line.len();
+    }
+
+    /// Returns the line and column of the next character
+    fn position(&self) -> (usize, usize) {
+        (self.line_number, self.pos + 1)
+    }
+
+    fn skip_whitespace(&mut self) -> io::Result<()> {
+        while let Some(c) = self.peek()? {
+            if !c.is_whitespace() && c != '\u{feff}' {
+                break;
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+}
+
+/// A line of moves being read: the main line or a variation
+struct Frame {
+    board: Board,
+    before_last_move: Option<Board>,
+    moves: Vec<PgnMove>,
+    pending_comments: Vec<String>,
+}
+
+impl Frame {
+    fn new(board: Board) -> Frame {
+        Frame {
+            board,
+            before_last_move: None,
+            moves: Vec::new(),
+            pending_comments: Vec::new(),
+        }
+    }
+
+    fn push_comment(&mut self, comment: String) {
+        match self.moves.last_mut() {
+            Some(last) => {
+                let text = last.extract_commands(&comment);
+                if !text.is_empty() || comment.is_empty() {
+                    last.comments.push(text);
+                }
+            }
+            None => self.pending_comments.push(comment),
+        }
+    }
+
+    fn push_nag(&mut self, nag: u8) {
+        if let Some(last) = self.moves.last_mut() {
+            last.nags.push(nag);
+        }
+    }
+}
+
+/// Streaming reader producing one `PgnGame` at a time
+/// Only the game currently being read is kept in memory, so arbitrarily large files can
+/// be processed. Moves are replayed on a `Board` starting from the `FEN` tag or the
+/// standard starting position. After an error the reader skips ahead to the next game,
+/// so iteration can continue past a broken game.
+pub struct PgnReader<R> {
+    lexer: Lexer<R>,
+    in_movetext: bool,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// Creates a reader, e.g. from a `BufReader<File>` or the bytes of a string
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            lexer: Lexer {
+                reader,
+                buffer: Vec::new(),
+                line: Vec::new(),
+                pos: 0,
+                line_number: 0,
+            },
+            in_movetext: false,
+            done: false,
+        }
+    }
+
+    /// Reads the next game, returning None at the end of the input
+    pub fn read_game(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        self.in_movetext = false;
+        self.lexer.skip_whitespace().map_err(|e| self.io_error(e))?;
+        if self.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut game = PgnGame::new();
+        let mut fen_position = self.lexer.position();
+
+        loop {
+            self.lexer.skip_whitespace().map_err(|e| self.io_error(e))?;
+            if self.peek()? != Some('[') {
+                break;
+            }
+            let position = self.lexer.position();
+            let (name, value) = self.read_tag()?;
+            if name == "FEN" {
+                fen_position = position;
+            }
+            game.tags.push((name, value));
+        }
+
+        self.in_movetext = true;
+        let board = game
+            .starting_board()
+            .map_err(|e| error(PgnErrorKind::InvalidFen(e), fen_position))?;
+        if let Some(result) = game.tag("Result").and_then(|r| r.parse().ok()) {
+            game.result = result;
+        }
+
+        let mut frames = vec![Frame::new(board)];
+
+        loop {
+            self.lexer.skip_whitespace().map_err(|e| self.io_error(e))?;
+            let position = self.lexer.position();
+            let Some(c) = self.peek()? else {
+                break;
+            };
+            let frame = frames.last_mut().unwrap();
+
+            match c {
+                // The next game starts without this one having a result token
+                '[' => break,
+                '{' => {
+                    let comment = self.read_brace_comment()?;
+                    frame.push_comment(comment);
+                }
+                ';' => {
+                    let comment: String = self.lexer.line[self.lexer.pos + 1..].iter().collect();
+                    self.lexer.skip_line();
+                    frame.push_comment(comment.trim().to_string());
+                }
+                '(' => {
+                    self.lexer.bump();
+                    let board = frame
+                        .before_last_move
+                        .clone()
+                        .ok_or_else(|| error(PgnErrorKind::VariationWithoutMove, position))?;
+                    frames.push(Frame::new(board));
+                }
+                ')' => {
+                    self.lexer.bump();
+                    if frames.len() == 1 {
+                        return Err(error(PgnErrorKind::UnbalancedVariation, position));
+                    }
+                    let variation = frames.pop().unwrap().moves;
+                    if !variation.is_empty() {
+                        let parent = frames.last_mut().unwrap();
+                        parent.moves.last_mut().unwrap().variations.push(variation);
+                    }
+                }
+                '$' => {
+                    self.lexer.bump();
+                    let digits = self.read_while(|c| c.is_ascii_digit())?;
+                    let nag = digits
+                        .parse()
+                        .map_err(|_| error(PgnErrorKind::InvalidNag, position))?;
+                    frames.last_mut().unwrap().push_nag(nag);
+                }
+                '.' => self.lexer.bump(),
+                c if is_symbol_char(c) => {
+                    let symbol = self.read_while(is_symbol_char)?;
+
+                    if let Ok(result) = symbol.parse::<GameResult>() {
+                        if frames.len() > 1 {
+                            return Err(error(PgnErrorKind::UnbalancedVariation, position));
+                        }
+                        game.result = result;
+                        break;
+                    }
+
+                    // Move numbers are implied by the position, skip them and their dots
+                    if symbol.chars().all(|c| c.is_ascii_digit()) {
+                        self.read_while(|c| c == '.')?;
+                        continue;
+                    }
+
+                    let san = symbol.trim_end_matches(['!', '?']);
+                    let suffix_nag = suffix_nag(&symbol[san.len()..]);
+                    let frame = frames.last_mut().unwrap();
+
+                    if !san.is_empty() {
+                        let mv = frame.board.parse_san(san).map_err(|e| {
+                            error(PgnErrorKind::InvalidMove(symbol.clone(), e), position)
+                        })?;
+                        let mut pgn_move = PgnMove::new(mv, frame.board.san(mv));
+                        pgn_move.comments_before = mem::take(&mut frame.pending_comments);
+                        frame.before_last_move = Some(frame.board.clone());
+                        frame.board.make_move(mv);
+                        frame.moves.push(pgn_move);
+                    }
+                    if let Some(nag) = suffix_nag {
+                        frame.push_nag(nag);
+                    }
+                }
+                c => return Err(error(PgnErrorKind::UnexpectedCharacter(c), position)),
+            }
+        }
+
+        if frames.len() > 1 {
+            return Err(error(
+                PgnErrorKind::UnbalancedVariation,
+                self.lexer.position(),
+            ));
+        }
+        game.moves = frames.pop().unwrap().moves;
+
+        Ok(Some(game))
+    }
+
+    fn peek(&mut self) -> Result<Option<char>, PgnError> {
+        self.lexer.peek().map_err(|e| self.io_error(e))
+    }
+
+    fn io_error(&self, e: io::Error) -> PgnError {
+        error(PgnErrorKind::Io(e.kind()), self.lexer.position())
+    }
+
+    fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> Result<String, PgnError> {
+        let mut text = String::new();
+        // Tokens never span lines, so stop at the end of the current one
+        while self.lexer.pos < self.lexer.line.len() {
+            let c = self.lexer.line[self.lexer.pos];
+            if !predicate(c) {
+                break;
+            }
+            text.push(c);
+            self.lexer.bump();
+        }
+        Ok(text)
+    }
+
+    /// Reads a `[Name "value"]` tag pair, unescaping `\"` and `\\` in the value
+    fn read_tag(&mut self) -> Result<(String, String), PgnError> {
+        let position = self.lexer.position();
+        let invalid = || error(PgnErrorKind::InvalidTag, position);
+
+        self.lexer.bump();
+        self.skip_inline_whitespace();
+        let name = self.read_while(|c| c.is_alphanumeric() || c == '_')?;
+        self.skip_inline_whitespace();
+        if name.is_empty() || self.current() != Some('"') {
+            return Err(invalid());
+        }
+        self.lexer.bump();
+
+        let mut value = String::new();
+        loop {
+            match self.current() {
+                Some('"') => break,
+                Some('\\') => {
+                    self.lexer.bump();
+                    value.push(self.current().ok_or_else(invalid)?);
+                }
+                Some(c) => value.push(c),
+                None => return Err(invalid()),
+            }
+            self.lexer.bump();
+        }
+        self.lexer.bump();
+
+        self.skip_inline_whitespace();
+        if self.current() != Some(']') {
+            return Err(invalid());
+        }
+        self.lexer.bump();
+
+        Ok((name, value))
+    }
+
+    /// Reads a `{ ... }` comment, which may span several lines
+    fn read_brace_comment(&mut self) -> Result<String, PgnError> {
+        let position = self.lexer.position();
+        self.lexer.bump();
+
+        let mut comment = String::new();
+        loop {
+            match self.peek()? {
+                Some('}') => break,
+                Some(c) => comment.push(c),
+                None => return Err(error(PgnErrorKind::UnterminatedComment, position)),
+            }
+            self.lexer.bump();
+        }
+        self.lexer.bump();
+
+        // Line breaks inside a comment are only there for wrapping
+        Ok(comment.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Returns the character at the current position without reading further lines
+    fn current(&self) -> Option<char> {
+        self.lexer.line.get(self.lexer.pos).copied()
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while self.current().is_some_and(|c| c == ' ' || c == '\t') {
+            self.lexer.bump();
+        }
+    }
+
+    /// Skips the rest of a broken game: its remaining tag pairs and movetext
+    fn skip_to_next_game(&mut self) -> io::Result<()> {
+        let mut seen_movetext = self.in_movetext;
+        self.lexer.skip_line();
+        while self.lexer.fill()? {
+            let first = self.lexer.line.iter().find(|c| !c.is_whitespace());
+            match first {
+                Some('[') if seen_movetext => return Ok(()),
+                Some('[') => {}
+                _ => seen_movetext = true,
+            }
+            self.lexer.skip_line();
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_game() {
+            Ok(Some(game)) => Some(Ok(game)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                let recovered = match e.kind {
+                    PgnErrorKind::Io(_) => false,
+                    _ => self.skip_to_next_game().is_ok(),
+                };
+                self.done = !recovered;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn error(kind: PgnErrorKind, (line, column): (usize, usize)) -> PgnError {
+    PgnError { kind, line, column }
+}
+
+/// Returns true for characters that can be part of a move, move number or result token
+fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-+#=:/*!?_".contains(c)
+}
+
+/// Converts a move suffix annotation like `!?` to its NAG
+fn suffix_nag(suffix: &str) -> Option<u8> {
+    match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(pgn: &str) -> Vec<Result<PgnGame, PgnError>> {
+        PgnReader::new(pgn.as_bytes()).collect()
+    }
+
+    fn sans(moves: &[PgnMove]) -> Vec<&str> {
+        moves.iter().map(|m| m.san.as_str()).collect()
+    }
+
+    #[test]
+    fn test_read_simple_game() {
+        let pgn = concat!(
+            "[Event \"Casual Game\"]\n",
+            "[Site \"Berlin GER\"]\n",
+            "[White \"Anderssen, Adolf\"]\n",
+            "[Result \"1-0\"]\n",
+            "\n",
+            "1. e4 e5 2. Nf3 Nc6 3.Bb5 a6 4. Ba4 Nf6 5. O-O 1-0\n",
+        );
+        let games = read_all(pgn);
+        assert_eq!(games.len(), 1);
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.tag("Event"), Some("Casual Game"));
+        assert_eq!(game.tag("White"), Some("Anderssen, Adolf"));
+        assert_eq!(game.tags.len(), 4);
+        assert_eq!(
+            sans(&game.moves),
+            vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O"]
+        );
+        assert!(game.moves[8].mv.is_castle());
+        assert_eq!(game.result, GameResult::WhiteWins);
+    }
+
+    #[test]
+    fn test_read_annotations() {
+        let pgn = concat!(
+            "{Opening comment} 1. e4! $14 {Best by test} e5?! 2. Nf3 ; rest of line\n",
+            "2... Nc6 $1 $2 *\n",
+        );
+        let game = read_all(pgn).remove(0).unwrap();
+        assert_eq!(game.moves[0].comments_before, vec!["Opening comment"]);
+        assert_eq!(game.moves[0].nags, vec![1, 14]);
+        assert_eq!(game.moves[0].comments, vec!["Best by test"]);
+        assert_eq!(game.moves[1].nags, vec![6]);
+        assert_eq!(game.moves[2].comments, vec!["rest of line"]);
+        assert_eq!(game.moves[3].nags, vec![1, 2]);
+        assert_eq!(game.result, GameResult::Ongoing);
+    }
+
+    #[test]
+    fn test_read_nested_variations() {
+        let pgn = "1. e4 e5 (1... c5 2. Nf3 (2. c3 d5) d6) (1... e6) 2. Nf3 1/2-1/2";
+        let game = read_all(pgn).remove(0).unwrap();
+        assert_eq!(sans(&game.moves), vec!["e4", "e5", "Nf3"]);
+
+        let variations = &game.moves[1].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(sans(&variations[0]), vec!["c5", "Nf3", "d6"]);
+        assert_eq!(sans(&variations[1]), vec!["e6"]);
+        assert_eq!(sans(&variations[0][1].variations[0]), vec!["c3", "d5"]);
+        assert_eq!(game.result, GameResult::Draw);
+    }
+
+    #[test]
+    fn test_read_from_fen_tag() {
+        let pgn = concat!(
+            "[SetUp \"1\"]\n",
+            "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n",
+            "\n",
+            "1. e4 Kd7 2. e5 0-1\n",
+        );
+        let game = read_all(pgn).remove(0).unwrap();
+        assert_eq!(sans(&game.moves), vec!["e4", "Kd7", "e5"]);
+        assert_eq!(
+            game.final_board().unwrap().to_fen(),
+            "8/3k4/8/4P3/8/8/8/4K3 b - - 0 2"
+        );
+    }
+
+    #[test]
+    fn test_read_multiple_games() {
+        let pgn = concat!(
+            "[Event \"A\"]\n\n1. e4 e5 1-0\n\n",
+            "[Event \"B\"]\n\n1. d4 d5 0-1\n\n",
+            "[Event \"C\"]\n\n1. c4 *\n",
+        );
+        let events: Vec<_> = read_all(pgn)
+            .into_iter()
+            .map(|game| game.unwrap().tag("Event").unwrap().to_string())
+            .collect();
+        assert_eq!(events, vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    fn test_read_escapes_and_missing_result() {
+        let pgn = concat!(
+            "[Event \"The \\\"Immortal\\\" \\\\ Game\"]\n",
+            "% this escaped line is ignored\n",
+            "1. e4 e5\n",
+            "[Event \"Next\"]\n",
+            "1. d4\n",
+        );
+        let games: Vec<_> = read_all(pgn).into_iter().map(Result::unwrap).collect();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("The \"Immortal\" \\ Game"));
+        assert_eq!(sans(&games[0].moves), vec!["e4", "e5"]);
+        assert_eq!(games[1].tag("Event"), Some("Next"));
+    }
+
+    #[test]
+    fn test_read_error_positions() {
+        for (pgn, kind, line, column) in [
+            (
+                "[Event \"A\"]\n\n1. e4 e5 2. Ke3 1-0",
+                PgnErrorKind::InvalidMove("Ke3".to_string(), ParseSanError::IllegalMove),
+                3,
+                13,
+            ),
+            ("[Event \"A]\n1. e4", PgnErrorKind::InvalidTag, 1, 1),
+            (
+                "1. e4 {never closed",
+                PgnErrorKind::UnterminatedComment,
+                1,
+                7,
+            ),
+            ("1. e4 e5 )", PgnErrorKind::UnbalancedVariation, 1, 10),
+            ("1. e4 (1. d4", PgnErrorKind::UnbalancedVariation, 1, 13),
+            ("(1. d4) 1. e4", PgnErrorKind::VariationWithoutMove, 1, 1),
+            ("1. e4 $x", PgnErrorKind::InvalidNag, 1, 7),
+            ("1. e4 & e5", PgnErrorKind::UnexpectedCharacter('&'), 1, 7),
+        ] {
+            let err = read_all(pgn).remove(0).unwrap_err();
+            assert_eq!(err.kind(), &kind, "{}", pgn);
+            assert_eq!((err.line(), err.column()), (line, column), "{}", pgn);
+        }
+
+        let err = read_all("[FEN \"8/8 w\"]\n\n*").remove(0).unwrap_err();
+        assert!(matches!(err.kind(), PgnErrorKind::InvalidFen(_)));
+        assert_eq!(
+            err.to_string(),
+            "Invalid PGN: invalid FEN tag: incorrect number of ranks at line 1, column 1"
+        );
+    }
+
+    #[test]
+    fn test_read_recovers_after_error() {
+        let pgn = concat!(
+            "[Event \"A\"]\n\n1. e4 e5 2. Qxf7 1-0\n\n",
+            "[Event \"B\"]\n\n1. d4 d5 0-1\n",
+        );
+        let games = read_all(pgn);
+        assert_eq!(games.len(), 2);
+        assert!(games[0].is_err());
+        assert_eq!(games[1].as_ref().unwrap().tag("Event"), Some("B"));
+    }
+
+    #[test]
+    fn test_read_latin1() {
+        let pgn = b"[Event \"Caf\xe9\"]\n\n1. e4 {G\xfcnther} e5 1-0\n\n\
+                    [Event \"B\"]\n\n1. d4 d5 0-1\n";
+        let games: Vec<_> = PgnReader::new(&pgn[..]).collect();
+        assert_eq!(games.len(), 2);
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.tag("Event"), Some("Caf\u{e9}"));
+        assert_eq!(first.moves[0].comments, ["G\u{fc}nther"]);
+        assert_eq!(sans(&first.moves), ["e4", "e5"]);
+        assert_eq!(sans(&games[1].as_ref().unwrap().moves), ["d4", "d5"]);
+    }
+
+    #[test]
+    fn test_read_io_error() {
+        struct FailingReader;
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk on fire"))
+            }
+        }
+
+        let games: Vec<_> = PgnReader::new(io::BufReader::new(FailingReader)).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(
+            games[0].as_ref().unwrap_err().kind(),
+            &PgnErrorKind::Io(io::ErrorKind::Other)
+        );
+    }
+
+    #[test]
+    fn test_read_empty_input() {
+        assert!(read_all("").is_empty());
+        assert!(read_all("  \n\n ").is_empty());
+    }
+}
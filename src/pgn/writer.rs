@@ -0,0 +1,359 @@
+use super::{PgnGame, PgnMove};
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    io::{self, Write},
+    mem,
+    time::Duration,
+};
+
+/// Maximum length of a movetext line, as recommended by the PGN export format
+const LINE_WIDTH: usize = 80;
+
+/// The Seven Tag Roster with the values written when a tag is missing
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// Movetext tokens, joined with spaces and wrapped when written
+#[derive(Default)]
+struct Tokens {
+    tokens: Vec<String>,
+    prefix: String,
+}
+
+impl Tokens {
+    fn push(&mut self, token: impl Into<String>) {
+        let mut token = token.into();
+        if !self.prefix.is_empty() {
+            token.insert_str(0, &mem::take(&mut self.prefix));
+        }
+        self.tokens.push(token);
+    }
+
+    fn open_variation(&mut self) {
+        self.prefix.push('(');
+    }
+
+    fn close_variation(&mut self) {
+        if let Some(last) = self.tokens.last_mut() {
+            last.push(')');
+        }
+    }
+
+    /// Pushes a comment one word at a time so it can be wrapped across lines
+    /// PGN has no escape for `}` inside a comment, so any are dropped.
+    fn push_comment(&mut self, comment: &str) {
+        let comment = comment.replace('}', " ");
+        let words: Vec<&str> = comment.split_whitespace().collect();
+        if words.is_empty() {
+            self.push("{}");
+            return;
+        }
+        for (i, word) in words.iter().enumerate() {
+            let open = if i == 0 { "{" } else { "" };
+            let close = if i == words.len() - 1 { "}" } else { "" };
+            self.push(format!("{}{}{}", open, word, close));
+        }
+    }
+
+    /// Joins the tokens into lines no longer than `width`, unless a single token is
+    /// A `%` in the first column makes readers skip the line, so no line starts with a
+    /// token beginning with `%`; the line is broken before an earlier token instead.
+    fn wrap(&self, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line: Vec<&str> = Vec::new();
+        let mut length = 0;
+        for token in &self.tokens {
+            if !line.is_empty() && length + 1 + token.len() > width {
+                let mut split = line.len();
+                let mut first = token.as_str();
+                while first.starts_with('%') && split > 1 {
+                    split -= 1;
+                    first = line[split];
+                }
+                if !first.starts_with('%') {
+                    let rest = line.split_off(split);
+                    lines.push(mem::replace(&mut line, rest).join(" "));
+                    length = line.iter().map(|token| token.len() + 1).sum::<usize>()
+                        - usize::from(!line.is_empty());
+                }
+            }
+            if !line.is_empty() {
+                length += 1;
+            }
+            line.push(token);
+            length += token.len();
+        }
+        if !line.is_empty() {
+            lines.push(line.join(" "));
+        }
+        lines
+    }
+}
+
+/// Pushes a line of moves starting at `ply`, counted in half-moves from White's first
+fn push_moves(tokens: &mut Tokens, moves: &[PgnMove], ply: usize) {
+    let mut needs_number = true;
+
+    for (i, pgn_move) in moves.iter().enumerate() {
+        let ply = ply + i;
+
+        for comment in &pgn_move.comments_before {
+            tokens.push_comment(comment);
+            needs_number = true;
+        }
+
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if needs_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(pgn_move.san.as_str());
+        for nag in &pgn_move.nags {
+            tokens.push(format!("${}", nag));
+        }
+
+        let mut commands = Vec::new();
+        if let Some(eval) = pgn_move.eval {
+            commands.push(format!("[%eval {}]", eval));
+        }
+        if let Some(clock) = pgn_move.clock {
+            commands.push(format!("[%clk {}]", format_clock(clock)));
+        }
+        let mut comments = pgn_move.comments.iter();
+        if !commands.is_empty() {
+            commands.extend(comments.next().cloned());
+            tokens.push_comment(&commands.join(" "));
+        }
+        for comment in comments {
+            tokens.push_comment(comment);
+        }
+
+        for variation in &pgn_move.variations {
+            tokens.open_variation();
+            push_moves(tokens, variation, ply);
+            tokens.close_variation();
+        }
+
+        needs_number = !commands.is_empty()
+            || !pgn_move.comments.is_empty()
+            || !pgn_move.variations.is_empty();
+    }
+}
+
+/// Formats a clock time as `h:mm:ss`, with fractional seconds only when present
+fn format_clock(clock: Duration) -> String {
+    let seconds = clock.as_secs();
+    let mut text = format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    let millis = clock.subsec_millis();
+    if millis != 0 {
+        text.push_str(format!(".{:03}", millis).trim_end_matches('0'));
+    }
+    text
+}
+
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes the game in PGN export format: the Seven Tag Roster first, then the other
+/// tags, then the movetext wrapped at 80 columns and terminated by the result
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.to_string(),
+                _ => self.tag(name).unwrap_or(default).to_string(),
+            };
+            writeln!(f, "[{} \"{}\"]", name, escape_tag_value(&value))?;
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name) {
+                writeln!(f, "[{} \"{}\"]", name, escape_tag_value(value))?;
+            }
+        }
+        writeln!(f)?;
+
+        let ply = self.starting_board().map_or(0, |board| {
+            (board.fullmove_number() as usize - 1) * 2 + board.side_to_move().index()
+        });
+        let mut tokens = Tokens::default();
+        push_moves(&mut tokens, &self.moves, ply);
+        tokens.push(self.result.to_string());
+
+        for line in tokens.wrap(LINE_WIDTH) {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes games to a stream in PGN export format, separated by blank lines
+pub struct PgnWriter<W> {
+    writer: W,
+    games_written: usize,
+}
+
+impl<W: Write> PgnWriter<W> {
+    pub fn new(writer: W) -> PgnWriter<W> {
+        PgnWriter {
+            writer,
+            games_written: 0,
+        }
+    }
+
+    pub fn write_game(&mut self, game: &PgnGame) -> io::Result<()> {
+        if self.games_written > 0 {
+            writeln!(self.writer)?;
+        }
+        write!(self.writer, "{}", game)?;
+        self.games_written += 1;
+        Ok(())
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::{PgnEval, PgnReader};
+    use crate::types::GameResult;
+
+    fn read(pgn: &str) -> PgnGame {
+        PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap()
+    }
+
+    fn assert_same_moves(written: &PgnGame, original: &PgnGame) {
+        assert_eq!(written.moves, original.moves);
+        assert_eq!(written.result, original.result);
+    }
+
+    #[test]
+    fn test_write_tag_order() {
+        let mut game =
+            read("[ECO \"C60\"]\n[White \"Morphy\"]\n[Event \"Opera \\\"Game\\\"\"]\n\n1. e4 1-0");
+        game.set_tag("Annotator", "Someone");
+        assert_eq!(
+            game.to_string(),
+            concat!(
+                "[Event \"Opera \\\"Game\\\"\"]\n",
+                "[Site \"?\"]\n",
+                "[Date \"????.??.??\"]\n",
+                "[Round \"?\"]\n",
+                "[White \"Morphy\"]\n",
+                "[Black \"?\"]\n",
+                "[Result \"1-0\"]\n",
+                "[ECO \"C60\"]\n",
+                "[Annotator \"Someone\"]\n",
+                "\n",
+                "1. e4 1-0\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_movetext() {
+        let pgn = concat!(
+            "{Start} 1. e4 e5 $1 2. Nf3 {Develops} Nc6 (2... d6 3. d4 (3. Bc4) 3... exd4) ",
+            "3. Bb5 { [%eval 0.25] [%clk 0:01:00.5] The Ruy } a6 { [%clk 0:00:59] } *",
+        );
+        let game = read(pgn);
+        assert_eq!(game.moves[4].eval, Some(PgnEval::Centipawns(25)));
+        assert_eq!(game.moves[4].comments, vec!["The Ruy"]);
+        assert_eq!(game.moves[5].clock, Some(Duration::from_secs(59)));
+        assert!(game.moves[5].comments.is_empty());
+
+        let text = game.to_string();
+        let movetext = text.split("\n\n").nth(1).unwrap().replace('\n', " ");
+        assert_eq!(
+            movetext.trim_end(),
+            concat!(
+                "{Start} 1. e4 e5 $1 2. Nf3 {Develops} 2... Nc6 (2... d6 3. d4 (3. Bc4) 3... exd4) ",
+                "3. Bb5 {[%eval 0.25] [%clk 0:01:00.5] The Ruy} 3... a6 {[%clk 0:00:59]} *",
+            )
+        );
+        assert_same_moves(&read(&text), &game);
+    }
+
+    #[test]
+    fn test_write_from_fen() {
+        let game = read("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\n12... Kd7 13. e4 0-1");
+        let text = game.to_string();
+        assert!(text.ends_with("\n\n12... Kd7 13. e4 0-1\n"), "{}", text);
+        assert_same_moves(&read(&text), &game);
+    }
+
+    #[test]
+    fn test_write_wraps_lines() {
+        let mut game = read(concat!(
+            "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 ",
+            "8. c3 O-O 9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 ",
+            "{A long comment that has to be spread over more than one line because it does ",
+            "not fit in the eighty columns allowed for a single line of movetext} 14. Bg5 *",
+        ));
+        game.result = GameResult::Draw;
+
+        let text = game.to_string();
+        for line in text.lines() {
+            assert!(line.len() <= 80, "{}", line);
+        }
+        assert!(text.lines().filter(|line| !line.starts_with('[')).count() > 3);
+        assert_same_moves(&read(&text), &game);
+    }
+
+    #[test]
+    fn test_write_comment_with_closing_brace() {
+        let mut game = read("1. e4 e5 *");
+        game.moves[0].comments = vec!["close } brace".to_string()];
+
+        let text = game.to_string();
+        assert!(text.contains("1. e4 {close brace} 1... e5"), "{}", text);
+        let written = read(&text);
+        assert_eq!(written.moves[0].comments, vec!["close brace"]);
+        assert_eq!(written.moves[1].san, "e5");
+    }
+
+    #[test]
+    fn test_write_never_starts_line_with_percent() {
+        let words: Vec<String> = (0..12).map(|i| format!("word{}", i)).collect();
+        let comment = format!("{} %trap next", words.join(" "));
+        let mut game = read("1. e4 e5 *");
+        game.moves[0].comments = vec![comment.clone()];
+
+        let text = game.to_string();
+        assert!(text.lines().all(|line| !line.starts_with('%')), "{}", text);
+        let written = read(&text);
+        assert_eq!(written.moves[0].comments, vec![comment]);
+        assert_eq!(written.moves[1].san, "e5");
+    }
+
+    #[test]
+    fn test_writer_separates_games() {
+        let mut writer = PgnWriter::new(Vec::new());
+        writer.write_game(&read("1. e4 1-0")).unwrap();
+        writer.write_game(&read("1. d4 0-1")).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+
+        let games: Vec<_> = PgnReader::new(output.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[1].moves[0].san, "d4");
+        assert!(output.contains("1. e4 1-0\n\n[Event \"?\"]"));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::board::Board;
+use crate::moves::Move;
+use crate::types::{PieceKind, Square};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+};
+
+/// Error returned when a SAN string cannot be matched to a legal move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseSanError {
+    /// The string is not valid SAN syntax
+    InvalidSyntax,
+    /// The syntax is valid but no legal move matches it
+    IllegalMove,
+    /// More than one legal move matches, the move needs more disambiguation
+    AmbiguousMove,
+}
+
+impl Display for ParseSanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            ParseSanError::InvalidSyntax => write!(f, "Invalid SAN: invalid syntax"),
+            ParseSanError::IllegalMove => write!(f, "Invalid SAN: illegal move"),
+            ParseSanError::AmbiguousMove => write!(f, "Invalid SAN: ambiguous move"),
+        }
+    }
+}
+
+impl Error for ParseSanError {}
+
+/// Returns the uppercase SAN letter of a piece kind
+fn piece_letter(kind: PieceKind) -> char {
+    match kind {
+        PieceKind::King => 'K',
+        PieceKind::Queen => 'Q',
+        PieceKind::Rook => 'R',
+        PieceKind::Bishop => 'B',
+        PieceKind::Knight => 'N',
+        PieceKind::Pawn => 'P',
+    }
+}
+
+/// Returns the piece kind for a SAN piece letter
+fn piece_from_letter(c: char) -> Option<PieceKind> {
+    match c {
+        'K' => Some(PieceKind::King),
+        'Q' => Some(PieceKind::Queen),
+        'R' => Some(PieceKind::Rook),
+        'B' => Some(PieceKind::Bishop),
+        'N' => Some(PieceKind::Knight),
+        _ => None,
+    }
+}
+
+impl Board {
+    /// Formats a legal move in Standard Algebraic Notation, e.g. `Nbd7`, `exd6`,
+    /// `e8=Q+` or `O-O-O#`
+    /// Output is strict: `x` for every capture, `=` before promotions, and only as much
+    /// disambiguation as the position requires.
+    pub fn san(&self, mv: Move) -> String {
+        let mut san = String::new();
+
+        if mv.is_castle() {
+            san.push_str(if mv.to().file() == 6 { "O-O" } else { "O-O-O" });
+        } else {
+            let kind = self
+                .piece_at(mv.from())
+                .expect("san called without a piece on the from square")
+                .kind();
+
+            if kind == PieceKind::Pawn {
+                if mv.is_capture() {
+                    san.push((b'a' + mv.from().file()) as char);
+                }
+            } else {
+                san.push(piece_letter(kind));
+
+                // Other pieces of the same kind that can also reach the target square
+                let rivals: Vec<Square> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|other| {
+                        other.to() == mv.to()
+                            && other.from() != mv.from()
+                            && self.piece_at(other.from()).map(|p| p.kind()) == Some(kind)
+                    })
+                    .map(|other| other.from())
+                    .collect();
+
+                if !rivals.is_empty() {
+                    let file = (b'a' + mv.from().file()) as char;
+                    let rank = (b'1' + mv.from().rank()) as char;
+                    if rivals.iter().all(|sq| sq.file() != mv.from().file()) {
+                        san.push(file);
+                    } else if rivals.iter().all(|sq| sq.rank() != mv.from().rank()) {
+                        san.push(rank);
+                    } else {
+                        san.push(file);
+                        san.push(rank);
+                    }
+                }
+            }
+
+            if mv.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&mv.to().to_string());
+
+            if let Some(kind) = mv.promotion() {
+                san.push('=');
+                san.push(piece_letter(kind));
+            }
+        }
+
+        let mut after = self.clone();
+        after.make_move(mv);
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+
+        san
+    }
+
+    /// Parses a move in Standard Algebraic Notation and returns the matching legal move
+    /// Parsing is lenient: surrounding whitespace, a missing `x`, missing or extra `+`/`#`,
+    /// annotations like `!?`, `0-0` for castling, an explicit `P` for pawn moves and
+    /// promotions without `=` are all accepted.
+    pub fn parse_san(&self, san: &str) -> Result<Move, ParseSanError> {
+        let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+        let castle_file = match san {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(file) = castle_file {
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|mv| mv.is_castle() && mv.to().file() == file)
+                .ok_or(ParseSanError::IllegalMove);
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+
+        let promotion = match chars.last().copied() {
+            Some(c) if chars.len() > 2 && "QRBNqrbn".contains(c) => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                piece_from_letter(c.to_ascii_uppercase())
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 {
+            return Err(ParseSanError::InvalidSyntax);
+        }
+        let to: Square = chars
+            .split_off(chars.len() - 2)
+            .into_iter()
+            .collect::<String>()
+            .parse()
+            .map_err(|_| ParseSanError::InvalidSyntax)?;
+
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        let mut rest = chars.into_iter().peekable();
+        let kind = match rest.peek().copied() {
+            Some('P') => {
+                rest.next();
+                PieceKind::Pawn
+            }
+            Some(c) => match piece_from_letter(c) {
+                Some(kind) => {
+                    rest.next();
+                    kind
+                }
+                None => PieceKind::Pawn,
+            },
+            None => PieceKind::Pawn,
+        };
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in rest {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(c as u8 - b'a')
+                }
+                '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+                _ => return Err(ParseSanError::InvalidSyntax),
+            }
+        }
+
+        let mut candidates = self.legal_moves().into_iter().filter(|mv| {
+            mv.to() == to
+                && !mv.is_castle()
+                && mv.promotion() == promotion
+                && self.piece_at(mv.from()).map(|p| p.kind()) == Some(kind)
+                && from_file.is_none_or(|file| mv.from().file() == file)
+                && from_rank.is_none_or(|rank| mv.from().rank() == rank)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err(ParseSanError::AmbiguousMove),
+            (None, _) => Err(ParseSanError::IllegalMove),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_move(board: &Board, uci: &str) -> Move {
+        *board
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_string() == uci)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_san_formatting() {
+        for (fen, uci, san) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e2e4",
+                "e4",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "g1f3",
+                "Nf3",
+            ),
+            // Disambiguation by file, by rank and by both
+            ("r3k3/1p1n4/8/8/8/8/8/4K3 b - - 0 1", "d7b6", "Nb6"),
+            ("r1b1kbnr/pp1n1ppp/8/8/8/8/8/4K3 b - - 0 1", "d7f6", "Ndf6"),
+            ("4k2K/8/8/8/8/1n3n2/8/6n1 b - - 0 1", "b3d2", "Nbd2"),
+            ("4k3/R7/8/8/8/8/R7/4K3 w - - 0 1", "a2a5", "R2a5"),
+            ("4k3/8/8/8/Q6Q/8/8/4K2Q w - - 0 1", "h4e4", "Qh4e4+"),
+            // Captures, en passant and promotions
+            (
+                "rnbqkb1r/ppppp1pp/7n/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "e5f6",
+                "exf6",
+            ),
+            ("1n5k/P7/8/8/8/8/8/7K w - - 0 1", "a7a8q", "a8=Q"),
+            ("1n5k/P7/8/8/8/8/8/7K w - - 0 1", "a7b8n", "axb8=N"),
+            // Castling, check and mate
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1c1", "O-O-O"),
+            ("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "a1a8", "Ra8+"),
+            ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"),
+            ("r3k3/8/8/8/8/8/8/3K4 b q - 0 1", "e8c8", "O-O-O+"),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.san(find_move(&board, uci)), san, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_san_pinned_piece_needs_no_disambiguation() {
+        // The knight on e2 is pinned, so only the knight on c1 can reach d3
+        let board = Board::from_fen("4r2k/8/8/8/8/8/4N3/2N1K3 w - - 0 1").unwrap();
+        assert_eq!(board.san(find_move(&board, "c1d3")), "Nd3");
+    }
+
+    #[test]
+    fn test_parse_san_lenient() {
+        let board =
+            Board::from_fen("r3k2r/p1pp1pb1/bn2Pnp1/2q1N3/1p2P3/2N5/PPPBBPPP/R3K2R b KQkq - 3 2")
+                .unwrap();
+        for (san, uci) in [
+            ("O-O", "e8g8"),
+            ("0-0", "e8g8"),
+            ("O-O-O", "e8c8"),
+            ("0-0-0+", "e8c8"),
+            ("fxe6", "f7e6"),
+            ("fe6", "f7e6"),
+            ("f7xe6", "f7e6"),
+            ("Qxe5", "c5e5"),
+            ("Qe5", "c5e5"),
+            ("Qxe5!?", "c5e5"),
+            ("Qe5++", "c5e5"),
+            ("Bf8", "g7f8"),
+            ("Bg7f8", "g7f8"),
+            ("Pd6", "d7d6"),
+            ("Pfxe6", "f7e6"),
+            ("Bf8 ", "g7f8"),
+            (" Qxe5+\n", "c5e5"),
+        ] {
+            assert_eq!(
+                board.parse_san(san).map(|mv| mv.to_string()),
+                Ok(uci.to_string()),
+                "{}",
+                san
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_san_promotions() {
+        let board = Board::from_fen("1n5k/P7/8/8/8/8/8/7K w - - 0 1").unwrap();
+        for (san, uci) in [
+            ("a8=Q", "a7a8q"),
+            ("a8Q", "a7a8q"),
+            ("a8=q", "a7a8q"),
+            ("axb8=N", "a7b8n"),
+            ("ab8R+", "a7b8r"),
+        ] {
+            assert_eq!(
+                board.parse_san(san).map(|mv| mv.to_string()),
+                Ok(uci.to_string()),
+                "{}",
+                san
+            );
+        }
+        // A promotion piece is required
+        assert_eq!(board.parse_san("a8"), Err(ParseSanError::IllegalMove));
+    }
+
+    #[test]
+    fn test_parse_san_errors() {
+        let board = Board::from_fen("4k2K/8/8/8/8/1n3n2/8/6n1 b - - 0 1").unwrap();
+        assert_eq!(board.parse_san("Nd2"), Err(ParseSanError::AmbiguousMove));
+        assert_eq!(board.parse_san("Na5").unwrap().to_string(), "b3a5");
+        assert_eq!(board.parse_san("Nbd4").unwrap().to_string(), "b3d4");
+        assert_eq!(board.parse_san("Qd4"), Err(ParseSanError::IllegalMove));
+        assert_eq!(board.parse_san("O-O"), Err(ParseSanError::IllegalMove));
+        assert_eq!(board.parse_san(""), Err(ParseSanError::InvalidSyntax));
+        assert_eq!(board.parse_san("Nz9"), Err(ParseSanError::InvalidSyntax));
+        assert_eq!(board.parse_san("Nbbd2"), Err(ParseSanError::InvalidSyntax));
+        assert_eq!(board.parse_san("hello"), Err(ParseSanError::InvalidSyntax));
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/8/Q6Q/8/8/4K2Q w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            for mv in board.legal_moves() {
+                let san = board.san(mv);
+                assert_eq!(board.parse_san(&san), Ok(mv), "{} {}", fen, san);
+            }
+        }
+    }
+}
@@ -0,0 +1,906 @@
+mod eval;
+mod picker;
+mod tt;
+
+pub use self::eval::{evaluate, piece_value};
+pub use self::tt::{Bound, TranspositionTable, TtEntry};
+
+use self::picker::{MoveHistory, MovePicker, is_quiet};
+use crate::board::Board;
+use crate::moves::Move;
+use crate::types::PieceKind;
+use std::time::{Duration, Instant};
+
+/// Score of being checkmated at the root, mates further away score closer to zero by one
+/// point per ply
+pub const MATE_SCORE: i32 = 30_000;
+/// Scores further from zero than this are mate scores
+const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY as i32;
+const INFINITY: i32 = MATE_SCORE + 1;
+/// Deepest ply the search reaches, bounding the depth of mate scores
+const MAX_PLY: usize = 128;
+/// How many nodes are searched between checks of the time and node limits
+const LIMIT_CHECK_INTERVAL: u64 = 1024;
+/// Transposition table size used by `Searcher::new`
+const DEFAULT_HASH_MEGABYTES: usize = 16;
+
+/// Returns true if the score announces a forced mate for either side
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() > MATE_BOUND
+}
+
+/// When to stop searching
+/// The search always completes depth 1 before honouring the node and time limits, so a
+/// move is found even with very small limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Maximum depth in plies
+    pub depth: u32,
+    /// Maximum number of nodes to search
+    pub nodes: Option<u64>,
+    /// Maximum time to spend on the search
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    /// Limits the search to the given depth
+    pub fn depth(depth: u32) -> SearchLimits {
+        SearchLimits {
+            depth,
+            ..SearchLimits::default()
+        }
+    }
+
+    /// Limits the search to the given number of nodes
+    pub fn nodes(nodes: u64) -> SearchLimits {
+        SearchLimits {
+            nodes: Some(nodes),
+            ..SearchLimits::default()
+        }
+    }
+
+    /// Limits the search to the given time
+    pub fn time(time: Duration) -> SearchLimits {
+        SearchLimits {
+            time: Some(time),
+            ..SearchLimits::default()
+        }
+    }
+}
+
+impl Default for SearchLimits {
+    /// No node or time limit and the maximum depth, so at least one limit should be set
+    fn default() -> Self {
+        SearchLimits {
+            depth: MAX_PLY as u32 - 1,
+            nodes: None,
+            time: None,
+        }
+    }
+}
+
+/// Tunable parameters of the selective search
+/// Depths are in plies and margins in centipawns. Each pruning, reduction and extension
+/// technique can be switched off on its own, e.g. to compare against the plain alpha-beta
+/// search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchParams {
+    /// Skip the turn in positions that look good enough to fail high anyway, and cut off
+    /// if a reduced search still fails high
+    pub null_move_pruning: bool,
+    /// Minimum remaining depth for null-move pruning
+    pub null_move_min_depth: u32,
+    /// Depth reduction of the null-move search, which grows by one every six plies of
+    /// remaining depth
+    pub null_move_reduction: u32,
+    /// Search quiet moves late in the move order with less depth, re-searching them at
+    /// full depth if they turn out to raise alpha
+    pub late_move_reductions: bool,
+    /// Minimum remaining depth for late move reductions
+    pub lmr_min_depth: u32,
+    /// Number of moves always searched at full depth
+    pub lmr_min_moves: u32,
+    /// The reduction is `lmr_base + ln(depth) * ln(move number) / lmr_divisor` plies
+    pub lmr_base: f64,
+    pub lmr_divisor: f64,
+    /// Skip quiet moves near the leaves when the static evaluation is too far below
+    /// alpha for them to catch up
+    pub futility_pruning: bool,
+    /// Maximum remaining depth for futility pruning
+    pub futility_max_depth: u32,
+    /// Margin per ply of remaining depth for futility pruning
+    pub futility_margin: i32,
+    /// Return the static evaluation near the leaves when it is so far above beta that
+    /// the opponent cannot catch up
+    pub reverse_futility_pruning: bool,
+    /// Maximum remaining depth for reverse futility pruning
+    pub reverse_futility_max_depth: u32,
+    /// Margin per ply of remaining depth for reverse futility pruning
+    pub reverse_futility_margin: i32,
+    /// Search moves that give check one ply deeper
+    pub check_extensions: bool,
+    /// Search each iteration with a narrow window around the previous iteration's score,
+    /// widening it whenever the score falls outside
+    pub aspiration_windows: bool,
+    /// Minimum depth of an iteration to use an aspiration window
+    pub aspiration_min_depth: u32,
+    /// Initial distance of the window bounds from the previous score, doubled after each
+    /// failed search
+    pub aspiration_window: i32,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams {
+            null_move_pruning: true,
+            null_move_min_depth: 3,
+            null_move_reduction: 2,
+            late_move_reductions: true,
+            lmr_min_depth: 3,
+            lmr_min_moves: 3,
+            lmr_base: 0.75,
+            lmr_divisor: 2.25,
+            futility_pruning: true,
+            futility_max_depth: 3,
+            futility_margin: 150,
+            reverse_futility_pruning: true,
+            reverse_futility_max_depth: 6,
+            reverse_futility_margin: 120,
+            check_extensions: true,
+            aspiration_windows: true,
+            aspiration_min_depth: 4,
+            aspiration_window: 40,
+        }
+    }
+}
+
+/// The outcome of a search: the best move found, its score and the expected line of play
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The best move, or None if the side to move has no legal moves
+    pub best_move: Option<Move>,
+    /// Score in centipawns from the side to move's point of view, see `mate_in`
+    pub score: i32,
+    /// Depth of the last completed iteration
+    pub depth: u32,
+    /// Principal variation, starting with the best move
+    pub pv: Vec<Move>,
+    /// Number of nodes searched
+    pub nodes: u64,
+}
+
+impl SearchResult {
+    /// Returns the number of moves until mate if the score is a mate score, negative when
+    /// the side to move is getting mated
+    pub fn mate_in(&self) -> Option<i32> {
+        if !is_mate_score(self.score) {
+            return None;
+        }
+        let plies = MATE_SCORE - self.score.abs();
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+/// Negamax alpha-beta search with iterative deepening
+/// A `Searcher` can be reused between searches; each search starts from the position it
+/// is given and only detects repetitions of positions reached during the search. Moves
+/// after the first at each node are searched with a null window and re-searched if they
+/// turn out better, and the techniques in `SearchParams` prune the rest of the tree.
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    nodes: u64,
+    limits: SearchLimits,
+    start: Instant,
+    stopped: bool,
+    /// Depth of the iteration in progress
+    iteration: u32,
+    /// Zobrist keys of the positions between the root and the current node
+    keys: Vec<u64>,
+    /// Moves played between the root and the current node, None for null moves
+    moves: Vec<Option<Move>>,
+    /// Principal variation of the previous iteration, searched first
+    pv_hint: Vec<Move>,
+    /// Whether the current node lies on `pv_hint`
+    follow_pv: bool,
+    table: TranspositionTable,
+    history: MoveHistory,
+    params: SearchParams,
+}
+
+impl Searcher {
+    /// Creates a searcher with a 16 MB transposition table
+    pub fn new() -> Searcher {
+        Searcher::with_hash_size(DEFAULT_HASH_MEGABYTES)
+    }
+
+    /// Creates a searcher whose transposition table uses at most `megabytes` of memory
+    pub fn with_hash_size(megabytes: usize) -> Searcher {
+        Searcher {
+            nodes: 0,
+            limits: SearchLimits::default(),
+            start: Instant::now(),
+            stopped: false,
+            iteration: 0,
+            keys: Vec::new(),
+            moves: Vec::new(),
+            pv_hint: Vec::new(),
+            follow_pv: false,
+            table: TranspositionTable::new(megabytes),
+            history: MoveHistory::new(),
+            params: SearchParams::default(),
+        }
+    }
+
+    /// Resizes the transposition table to at most `megabytes` of memory, clearing it
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.table.resize(megabytes);
+    }
+
+    pub fn params(&self) -> &SearchParams {
+        &self.params
+    }
+
+    /// Replaces the parameters of the selective search, taking effect from the next search
+    pub fn set_params(&mut self, params: SearchParams) {
+        self.params = params;
+    }
+
+    /// Forgets the results of earlier searches, e.g. before starting a new game
+    pub fn clear_hash(&mut self) {
+        self.table.clear();
+        self.history.clear();
+    }
+
+    /// Returns how full the transposition table is in permille
+    pub fn hashfull(&self) -> u32 {
+        self.table.hashfull()
+    }
+
+    /// Searches the position until a limit is reached and returns the result of the
+    /// deepest completed iteration
+    pub fn search(&mut self, board: &Board, limits: &SearchLimits) -> SearchResult {
+        self.nodes = 0;
+        self.limits = *limits;
+        self.start = Instant::now();
+        self.stopped = false;
+        self.keys.clear();
+        self.moves.clear();
+        self.table.new_search();
+        self.history.new_search();
+
+        let mut board = board.clone();
+        let mut result = SearchResult {
+            best_move: board.legal_moves().first().copied(),
+            score: 0,
+            depth: 0,
+            pv: Vec::new(),
+            nodes: 0,
+        };
+
+        for depth in 1..=limits.depth.max(1) {
+            self.iteration = depth;
+            let mut pv = Vec::new();
+            self.pv_hint.clone_from(&result.pv);
+            let score = self.aspiration_search(&mut board, depth, result.score, &mut pv);
+
+            if self.stopped {
+                break;
+            }
+            result = SearchResult {
+                best_move: pv.first().copied().or(result.best_move),
+                score,
+                depth,
+                pv,
+                nodes: self.nodes,
+            };
+
+            // A forced mate within the searched depth cannot improve by searching deeper
+            if is_mate_score(score) && MATE_SCORE - score.abs() <= depth as i32 {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Searches the root at `depth` with a window around the previous iteration's score,
+    /// widening the side the score fell out of until it lands inside
+    fn aspiration_search(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        previous_score: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        let params = self.params;
+        if !params.aspiration_windows
+            || depth < params.aspiration_min_depth
+            || is_mate_score(previous_score)
+        {
+            self.follow_pv = true;
+            return self.negamax(board, depth, 0, -INFINITY, INFINITY, pv);
+        }
+
+        let mut delta = params.aspiration_window.max(1);
+        let mut alpha = (previous_score - delta).max(-INFINITY);
+        let mut beta = (previous_score + delta).min(INFINITY);
+        loop {
+            self.follow_pv = true;
+            let score = self.negamax(board, depth, 0, alpha, beta, pv);
+            if self.stopped {
+                return score;
+            }
+
+            delta = delta.saturating_mul(2);
+            if score <= alpha {
+                alpha = alpha.saturating_sub(delta).max(-INFINITY);
+            } else if score >= beta {
+                beta = beta.saturating_add(delta).min(INFINITY);
+            } else {
+                return score;
+            }
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(LIMIT_CHECK_INTERVAL) {
+            self.check_limits();
+        }
+        if self.stopped {
+            return 0;
+        }
+
+        if ply > 0 && self.is_draw(board) {
+            return 0;
+        }
+
+        let key = board.zobrist_key();
+        let entry = self.table.probe(key, ply);
+        if let Some(entry) = entry
+            && ply > 0
+            && entry.depth() >= depth
+        {
+            let score = entry.score();
+            match entry.bound() {
+                Bound::Exact => {
+                    *pv = self.pv_from_table(board, depth);
+                    return score;
+                }
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate(board);
+        }
+
+        let params = self.params;
+        let pv_node = beta - alpha > 1;
+        let in_check = board.in_check();
+        let static_eval = if in_check { -INFINITY } else { evaluate(board) };
+        let previous = self.moves.last().copied().flatten();
+        let mut child_pv = Vec::new();
+
+        // Pruning is only safe away from the principal variation and from mate scores
+        let can_prune = !pv_node && !in_check && !is_mate_score(beta);
+
+        if can_prune
+            && params.reverse_futility_pruning
+            && depth <= params.reverse_futility_max_depth
+            && static_eval - params.reverse_futility_margin * depth as i32 >= beta
+        {
+            return static_eval;
+        }
+
+        // Passing is worse than the best move unless the side to move is in zugzwang, which
+        // is only likely with nothing but pawns left, so never pass in that case or twice in
+        // a row
+        if can_prune
+            && params.null_move_pruning
+            && depth >= params.null_move_min_depth
+            && static_eval >= beta
+            && self.moves.last() != Some(&None)
+            && has_non_pawn_material(board)
+        {
+            let reduction = params.null_move_reduction + depth / 6;
+            self.follow_pv = false;
+            self.keys.push(key);
+            self.moves.push(None);
+            let undo = board.make_null_move();
+            let score = -self.negamax(
+                board,
+                depth.saturating_sub(1 + reduction),
+                ply + 1,
+                -beta,
+                -beta + 1,
+                &mut child_pv,
+            );
+            board.unmake_null_move(undo);
+            self.moves.pop();
+            self.keys.pop();
+
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                // A mate found after passing does not prove a mate with a real move
+                return if is_mate_score(score) { beta } else { score };
+            }
+        }
+
+        let futile = can_prune
+            && params.futility_pruning
+            && depth <= params.futility_max_depth
+            && !is_mate_score(alpha)
+            && static_eval + params.futility_margin * depth as i32 <= alpha;
+
+        let following_pv = self.follow_pv;
+        let pv_move = self.pv_hint.get(ply).copied().filter(|_| following_pv);
+        let hash_move = pv_move.or(entry.and_then(|entry| entry.best_move()));
+        let mut picker = MovePicker::new(hash_move, ply, previous);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut quiets_tried = Vec::new();
+        let mut moves_searched = 0;
+
+        while let Some(mv) = picker.next(board, &self.history) {
+            let quiet = is_quiet(mv);
+            self.follow_pv = following_pv && Some(mv) == pv_move;
+            self.keys.push(key);
+            self.moves.push(Some(mv));
+            let undo = board.make_move(mv);
+            let gives_check = board.in_check();
+
+            if futile && moves_searched > 0 && quiet && !gives_check {
+                board.unmake_move(undo);
+                self.moves.pop();
+                self.keys.pop();
+                continue;
+            }
+
+            let new_depth = if params.check_extensions && gives_check {
+                depth
+            } else {
+                depth - 1
+            };
+            let score = if moves_searched == 0 {
+                -self.negamax(board, new_depth, ply + 1, -beta, -alpha, &mut child_pv)
+            } else {
+                let reduction = if params.late_move_reductions
+                    && depth >= params.lmr_min_depth
+                    && moves_searched >= params.lmr_min_moves
+                    && quiet
+                    && !in_check
+                    && !gives_check
+                {
+                    let reduction = late_move_reduction(&params, depth, moves_searched);
+                    let reduction = if pv_node {
+                        reduction.saturating_sub(1)
+                    } else {
+                        reduction
+                    };
+                    reduction.min(new_depth.saturating_sub(1))
+                } else {
+                    0
+                };
+
+                let mut score = -self.negamax(
+                    board,
+                    new_depth - reduction,
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                    &mut child_pv,
+                );
+                if reduction > 0 && score > alpha {
+                    score =
+                        -self.negamax(board, new_depth, ply + 1, -alpha - 1, -alpha, &mut child_pv);
+                }
+                if score > alpha && score < beta {
+                    score = -self.negamax(board, new_depth, ply + 1, -beta, -alpha, &mut child_pv);
+                }
+                score
+            };
+            board.unmake_move(undo);
+            self.moves.pop();
+            self.keys.pop();
+            moves_searched += 1;
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(mv);
+                    pv.extend_from_slice(&child_pv);
+                }
+            }
+            if alpha >= beta {
+                if quiet {
+                    self.history
+                        .record_cutoff(board, ply, depth, mv, previous, &quiets_tried);
+                }
+                break;
+            }
+            if quiet {
+                quiets_tried.push(mv);
+            }
+        }
+
+        if best_move.is_none() {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table
+            .store(key, ply, depth, bound, best_score, best_move);
+
+        best_score
+    }
+
+    /// Searches captures and promotions until the position is quiet, so the static
+    /// evaluation is never taken in the middle of an exchange
+    /// The side to move may stand pat on the static evaluation unless it is in check, in
+    /// which case every evasion is searched. Captures that lose material according to
+    /// `Board::see` are skipped, and stalemates are only detected in the main search.
+    fn quiescence(&mut self, board: &mut Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(LIMIT_CHECK_INTERVAL) {
+            self.check_limits();
+        }
+        if self.stopped {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate(board);
+        }
+
+        let in_check = board.in_check();
+        let mut best_score = -INFINITY;
+        let mut picker = if in_check {
+            MovePicker::new(None, ply, None)
+        } else {
+            best_score = evaluate(board);
+            if best_score >= beta {
+                return best_score;
+            }
+            alpha = alpha.max(best_score);
+            MovePicker::captures()
+        };
+
+        while let Some(mv) = picker.next(board, &self.history) {
+            let undo = board.make_move(mv);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move(undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                alpha = alpha.max(score);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        // In check without a legal move, as quiet moves are searched there
+        if best_score == -INFINITY {
+            return -MATE_SCORE + ply as i32;
+        }
+        best_score
+    }
+
+    /// Follows the best moves stored in the transposition table for up to `depth` plies,
+    /// used to recover the principal variation below an exact table hit
+    fn pv_from_table(&self, board: &mut Board, depth: u32) -> Vec<Move> {
+        let mut pv = Vec::new();
+        let mut undos = Vec::new();
+
+        while pv.len() < depth as usize {
+            let Some(mv) = self
+                .table
+                .probe(board.zobrist_key(), 0)
+                .and_then(|entry| entry.best_move())
+                .filter(|&mv| board.is_legal(mv))
+            else {
+                break;
+            };
+            pv.push(mv);
+            undos.push(board.make_move(mv));
+        }
+
+        for undo in undos.into_iter().rev() {
+            board.unmake_move(undo);
+        }
+        pv
+    }
+
+    /// Returns true for draws by the fifty-move rule, insufficient material or a
+    /// repetition of a position earlier in the search
+    fn is_draw(&self, board: &Board) -> bool {
+        if board.is_fifty_move_rule() || board.is_insufficient_material() {
+            return true;
+        }
+
+        let key = board.zobrist_key();
+        self.keys
+            .iter()
+            .rev()
+            .take(board.halfmove_clock() as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|&earlier| earlier == key)
+    }
+
+    fn check_limits(&mut self) {
+        // Depth 1 always completes, so there is a searched move to return
+        if self.iteration <= 1 {
+            return;
+        }
+        let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes);
+        let out_of_time = self
+            .limits
+            .time
+            .is_some_and(|time| self.start.elapsed() >= time);
+        self.stopped = out_of_nodes || out_of_time;
+    }
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns true if the side to move has a piece other than pawns and its king
+fn has_non_pawn_material(board: &Board) -> bool {
+    let color = board.side_to_move();
+    !(board.by_color(color) & !board.by_kind(PieceKind::Pawn) & !board.by_kind(PieceKind::King))
+        .is_empty()
+}
+
+/// Returns how many plies less to search the move at index `moves_searched` of a node
+/// with `depth` remaining
+fn late_move_reduction(params: &SearchParams, depth: u32, moves_searched: u32) -> u32 {
+    let reduction = params.lmr_base
+        + (depth as f64).ln() * (moves_searched as f64).ln() / params.lmr_divisor.max(f64::EPSILON);
+    reduction.max(0.0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(fen: &str, depth: u32) -> SearchResult {
+        let board = Board::from_fen(fen).unwrap();
+        Searcher::new().search(&board, &SearchLimits::depth(depth))
+    }
+
+    fn uci(moves: &[Move]) -> Vec<String> {
+        moves.iter().map(Move::to_string).collect()
+    }
+
+    #[test]
+    fn test_search_mate_in_one() {
+        let result = search(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            3,
+        );
+        assert_eq!(result.best_move.unwrap().to_string(), "h5f7");
+        assert_eq!(result.mate_in(), Some(1));
+        assert_eq!(result.score, MATE_SCORE - 1);
+    }
+
+    #[test]
+    fn test_search_mate_in_two() {
+        let result = search("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1", 4);
+        assert_eq!(result.mate_in(), Some(2));
+        assert_eq!(result.pv.len(), 3);
+
+        // The defending side sees the mate coming
+        let result = search("6k1/R7/8/8/8/8/8/1R4K1 b - - 0 1", 3);
+        assert_eq!(result.mate_in(), Some(-1));
+    }
+
+    #[test]
+    fn test_search_wins_material() {
+        // The queen on d5 is hanging to the knight
+        let result = search("4k3/8/8/3q4/8/4N3/4P3/4K3 w - - 0 1", 2);
+        assert_eq!(result.best_move.unwrap().to_string(), "e3d5");
+        assert_eq!(result.score, 420);
+        assert_eq!(result.depth, 2);
+    }
+
+    #[test]
+    fn test_search_quiescence() {
+        // Taking the pawn looks good at depth 1 but loses the queen to the recapture
+        let result = search("4k3/8/3p4/4p3/8/8/4Q3/4K3 w - - 0 1", 1);
+        assert_ne!(result.best_move.unwrap().to_string(), "e2e5");
+        assert_eq!(result.score, 700);
+    }
+
+    #[test]
+    fn test_search_pv_is_legal() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let result = search(fen, 3);
+        assert_eq!(result.pv.first().copied(), result.best_move);
+
+        let mut board = Board::from_fen(fen).unwrap();
+        for mv in &result.pv {
+            assert!(board.legal_moves().contains(mv), "{:?}", uci(&result.pv));
+            board.make_move(*mv);
+        }
+    }
+
+    #[test]
+    fn test_search_no_moves() {
+        let result = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, 0);
+
+        let result = search("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.mate_in(), Some(0));
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let board = Board::startpos();
+        let mut searcher = Searcher::new();
+
+        let result = searcher.search(&board, &SearchLimits::nodes(500));
+        assert!(result.best_move.is_some());
+        assert!(result.nodes < 2000);
+
+        let result = searcher.search(&board, &SearchLimits::time(Duration::from_millis(50)));
+        assert!(result.best_move.is_some());
+        assert!(result.depth >= 1);
+
+        // Depth 1 completes even when it takes more nodes than the limit check interval
+        let board = Board::from_fen("k7/8/1qqqqqq1/8/8/1QQQQQQ1/8/K7 w - - 0 1").unwrap();
+        for limits in [SearchLimits::nodes(1), SearchLimits::time(Duration::ZERO)] {
+            let result = Searcher::new().search(&board, &limits);
+            assert_eq!(result.depth, 1);
+            assert_eq!(result.best_move.unwrap().to_string(), "b3b6");
+            assert_eq!(result.score, 900);
+            assert!(result.nodes > LIMIT_CHECK_INTERVAL);
+        }
+    }
+
+    #[test]
+    fn test_search_transposition_table() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let mut searcher = Searcher::new();
+        let first = searcher.search(&board, &SearchLimits::depth(3));
+
+        // The table survives between searches, so repeating one is much cheaper
+        let second = searcher.search(&board, &SearchLimits::depth(3));
+        assert_eq!(second.score, first.score);
+        assert!(second.nodes < first.nodes);
+
+        searcher.clear_hash();
+        assert_eq!(searcher.hashfull(), 0);
+        let cleared = searcher.search(&board, &SearchLimits::depth(3));
+        assert_eq!(cleared.nodes, first.nodes);
+
+        let mut small = Searcher::with_hash_size(0);
+        assert_eq!(
+            small.search(&board, &SearchLimits::depth(3)).score,
+            first.score
+        );
+        assert_eq!(small.hashfull(), 1000);
+    }
+
+    fn plain_params() -> SearchParams {
+        SearchParams {
+            null_move_pruning: false,
+            late_move_reductions: false,
+            futility_pruning: false,
+            reverse_futility_pruning: false,
+            check_extensions: false,
+            aspiration_windows: false,
+            ..SearchParams::default()
+        }
+    }
+
+    #[test]
+    fn test_search_selectivity() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let mut selective = Searcher::new();
+        let mut plain = Searcher::new();
+        plain.set_params(plain_params());
+        assert!(!plain.params().null_move_pruning);
+
+        let pruned = selective.search(&board, &SearchLimits::depth(5));
+        let full = plain.search(&board, &SearchLimits::depth(5));
+        assert!(
+            pruned.nodes * 2 < full.nodes,
+            "{} {}",
+            pruned.nodes,
+            full.nodes
+        );
+
+        // Pruning does not hide forced mates
+        for params in [SearchParams::default(), plain_params()] {
+            let mut searcher = Searcher::new();
+            searcher.set_params(params);
+            let board = Board::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+            let result = searcher.search(&board, &SearchLimits::depth(5));
+            assert_eq!(result.mate_in(), Some(2));
+        }
+    }
+
+    #[test]
+    fn test_search_aspiration_windows() {
+        // The score jumps from the material balance to a mate score, failing high out of
+        // the narrow window
+        let board = Board::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+        let mut searcher = Searcher::new();
+        searcher.set_params(SearchParams {
+            aspiration_min_depth: 1,
+            aspiration_window: 1,
+            ..SearchParams::default()
+        });
+        let result = searcher.search(&board, &SearchLimits::depth(6));
+        assert_eq!(result.mate_in(), Some(2));
+        assert_eq!(result.pv.len(), 3);
+    }
+
+    #[test]
+    fn test_null_move_zugzwang_guard() {
+        assert!(has_non_pawn_material(&Board::startpos()));
+        // White only has pawns, so passing is never tried
+        let board = Board::from_fen("4k3/4p3/8/8/8/8/4P3/3nK3 w - - 0 1").unwrap();
+        assert!(!has_non_pawn_material(&board));
+    }
+
+    #[test]
+    fn test_late_move_reduction() {
+        let params = SearchParams::default();
+        assert_eq!(late_move_reduction(&params, 3, 3), 1);
+        assert!(late_move_reduction(&params, 20, 30) > late_move_reduction(&params, 5, 5));
+    }
+}
@@ -0,0 +1,53 @@
+use crate::board::Board;
+use crate::types::{PieceColor, PieceKind};
+
+/// Returns the conventional value of a piece in centipawns, with the king valued at zero
+pub fn piece_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::King => 0,
+        PieceKind::Queen => 900,
+        PieceKind::Rook => 500,
+        PieceKind::Bishop => 330,
+        PieceKind::Knight => 320,
+        PieceKind::Pawn => 100,
+    }
+}
+
+/// Returns the material balance in centipawns from the side to move's point of view
+pub fn evaluate(board: &Board) -> i32 {
+    let us = board.side_to_move();
+    let balance: i32 = PieceKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let white = board.pieces(PieceColor::White, kind).count() as i32;
+            let black = board.pieces(PieceColor::Black, kind).count() as i32;
+            (white - black) * piece_value(kind)
+        })
+        .sum();
+
+    match us {
+        PieceColor::White => balance,
+        PieceColor::Black => -balance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_material() {
+        assert_eq!(evaluate(&Board::startpos()), 0);
+
+        // White is a knight up, seen from either side
+        let fen = "rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+        assert_eq!(
+            evaluate(&Board::from_fen(&format!("{} w", fen)).unwrap()),
+            320
+        );
+        assert_eq!(
+            evaluate(&Board::from_fen(&format!("{} b", fen)).unwrap()),
+            -320
+        );
+    }
+}
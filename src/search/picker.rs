@@ -0,0 +1,388 @@
+use super::MAX_PLY;
+use super::eval::piece_value;
+use crate::board::Board;
+use crate::moves::Move;
+use crate::types::{PieceColor, PieceKind};
+
+/// Largest magnitude a history score can reach
+const MAX_HISTORY: i32 = 16_384;
+
+/// Returns true for moves that are neither captures nor promotions
+pub(super) fn is_quiet(mv: Move) -> bool {
+    !mv.is_capture() && !mv.is_promotion()
+}
+
+fn butterfly_index(mv: Move) -> usize {
+    mv.from().index() * 64 + mv.to().index()
+}
+
+/// Statistics about quiet moves that caused beta cutoffs, used to order quiet moves
+#[derive(Debug, Clone)]
+pub(super) struct MoveHistory {
+    /// Up to two quiet moves per ply that recently caused a cutoff at that ply
+    killers: Vec<[Option<Move>; 2]>,
+    /// The quiet move that last refuted each move, indexed by the refuted move's squares
+    counter_moves: Vec<Option<Move>>,
+    /// Score of each quiet move by side to move and origin and target square
+    scores: Vec<i32>,
+}
+
+impl MoveHistory {
+    pub(super) fn new() -> MoveHistory {
+        MoveHistory {
+            killers: vec![[None; 2]; MAX_PLY],
+            counter_moves: vec![None; 64 * 64],
+            scores: vec![0; 2 * 64 * 64],
+        }
+    }
+
+    /// Forgets everything learned
+    pub(super) fn clear(&mut self) {
+        *self = MoveHistory::new();
+    }
+
+    /// Prepares for a new search, forgetting the killers and fading the scores so recent
+    /// cutoffs weigh more
+    pub(super) fn new_search(&mut self) {
+        self.killers.fill([None; 2]);
+        for score in &mut self.scores {
+            *score /= 2;
+        }
+    }
+
+    fn killers(&self, ply: usize) -> [Option<Move>; 2] {
+        self.killers[ply]
+    }
+
+    fn counter_move(&self, previous: Option<Move>) -> Option<Move> {
+        previous.and_then(|previous| self.counter_moves[butterfly_index(previous)])
+    }
+
+    fn score(&self, color: PieceColor, mv: Move) -> i32 {
+        self.scores[color.index() * 64 * 64 + butterfly_index(mv)]
+    }
+
+    /// Moves a score towards `MAX_HISTORY` or `-MAX_HISTORY` by `bonus`, slowing down as
+    /// it gets closer
+    fn update_score(&mut self, color: PieceColor, mv: Move, bonus: i32) {
+        let score = &mut self.scores[color.index() * 64 * 64 + butterfly_index(mv)];
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+
+    /// Records that the quiet move `mv` caused a beta cutoff at `ply` with `depth`
+    /// remaining, after the quiet moves in `tried` failed to
+    pub(super) fn record_cutoff(
+        &mut self,
+        board: &Board,
+        ply: usize,
+        depth: u32,
+        mv: Move,
+        previous: Option<Move>,
+        tried: &[Move],
+    ) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        if let Some(previous) = previous {
+            self.counter_moves[butterfly_index(previous)] = Some(mv);
+        }
+
+        let color = board.side_to_move();
+        let bonus = (depth * depth).min(MAX_HISTORY as u32) as i32;
+        self.update_score(color, mv, bonus);
+        for &failed in tried {
+            self.update_score(color, failed, -bonus);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    GenerateCaptures,
+    GoodCaptures,
+    Refutations,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Yields the legal moves of a position in stages, best first, generating each stage
+/// only when the previous ones did not cause a cutoff
+/// The order is the hash move, captures that do not lose material ordered by most
+/// valuable victim and least valuable attacker, the killer moves and counter-move, the
+/// remaining quiet moves ordered by history score, and finally the losing captures.
+#[derive(Debug, Clone)]
+pub(super) struct MovePicker {
+    stage: Stage,
+    hash_move: Option<Move>,
+    ply: usize,
+    previous: Option<Move>,
+    captures_only: bool,
+    /// Moves of the current stage with their ordering scores
+    moves: Vec<(Move, i32)>,
+    /// Killer moves and counter-move, already yielded during the refutation stage
+    refutations: Vec<Move>,
+    bad_captures: Vec<Move>,
+    index: usize,
+}
+
+impl MovePicker {
+    /// Creates a picker for all moves, searching `hash_move` first
+    /// `previous` is the move that led to the position, used to look up the counter-move.
+    pub(super) fn new(hash_move: Option<Move>, ply: usize, previous: Option<Move>) -> MovePicker {
+        MovePicker {
+            stage: Stage::HashMove,
+            hash_move,
+            ply,
+            previous,
+            captures_only: false,
+            moves: Vec::new(),
+            refutations: Vec::new(),
+            bad_captures: Vec::new(),
+            index: 0,
+        }
+    }
+
+    /// Creates a picker for the captures and promotions that do not lose material
+    pub(super) fn captures() -> MovePicker {
+        MovePicker {
+            stage: Stage::GenerateCaptures,
+            captures_only: true,
+            ..MovePicker::new(None, 0, None)
+        }
+    }
+
+    /// Returns the next legal move, or None once all stages are exhausted
+    pub(super) fn next(&mut self, board: &mut Board, history: &MoveHistory) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::GenerateCaptures;
+                    if let Some(mv) = self.hash_move
+                        && board.is_pseudo_legal(mv)
+                        && board.keeps_king_safe(mv)
+                    {
+                        return Some(mv);
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    self.moves = board
+                        .pseudo_legal_captures()
+                        .into_iter()
+                        .filter(|&mv| Some(mv) != self.hash_move)
+                        .map(|mv| (mv, capture_score(board, mv)))
+                        .collect();
+                    self.index = 0;
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => {
+                    let Some(mv) = self.select_best() else {
+                        if self.captures_only {
+                            self.stage = Stage::Done;
+                        } else {
+                            self.stage = Stage::Refutations;
+                            self.refutations = self.find_refutations(board, history);
+                            self.index = 0;
+                        }
+                        continue;
+                    };
+                    if board.see(mv) < 0 {
+                        // Losing captures are only searched after the quiet moves
+                        if !self.captures_only {
+                            self.bad_captures.push(mv);
+                        }
+                    } else if board.keeps_king_safe(mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::Refutations => {
+                    let Some(&mv) = self.refutations.get(self.index) else {
+                        self.stage = Stage::GenerateQuiets;
+                        continue;
+                    };
+                    self.index += 1;
+                    if board.keeps_king_safe(mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    let color = board.side_to_move();
+                    self.moves = board
+                        .pseudo_legal_quiets()
+                        .into_iter()
+                        .filter(|&mv| Some(mv) != self.hash_move && !self.refutations.contains(&mv))
+                        .map(|mv| (mv, history.score(color, mv)))
+                        .collect();
+                    self.index = 0;
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => {
+                    let Some(mv) = self.select_best() else {
+                        self.stage = Stage::BadCaptures;
+                        self.index = 0;
+                        continue;
+                    };
+                    if board.keeps_king_safe(mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::BadCaptures => {
+                    let Some(&mv) = self.bad_captures.get(self.index) else {
+                        self.stage = Stage::Done;
+                        continue;
+                    };
+                    self.index += 1;
+                    if board.keeps_king_safe(mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    /// Moves the highest scoring remaining move of the current stage to the front and
+    /// returns it, so moves are only sorted as far as they are needed
+    fn select_best(&mut self) -> Option<Move> {
+        let remaining = self.moves.get_mut(self.index..)?;
+        let best = remaining
+            .iter()
+            .enumerate()
+            .max_by_key(|&(index, &(_, score))| (score, std::cmp::Reverse(index)))?
+            .0;
+        remaining.swap(0, best);
+        self.index += 1;
+        Some(remaining[0].0)
+    }
+
+    /// Returns the killer moves and counter-move that are pseudo-legal quiet moves in the
+    /// position, without duplicates or the hash move
+    fn find_refutations(&self, board: &Board, history: &MoveHistory) -> Vec<Move> {
+        let [first, second] = history.killers(self.ply);
+        let mut refutations = Vec::new();
+        for mv in [first, second, history.counter_move(self.previous)]
+            .into_iter()
+            .flatten()
+        {
+            if is_quiet(mv)
+                && Some(mv) != self.hash_move
+                && !refutations.contains(&mv)
+                && board.is_pseudo_legal(mv)
+            {
+                refutations.push(mv);
+            }
+        }
+        refutations
+    }
+}
+
+/// Orders captures by most valuable victim, then least valuable attacker, counting the
+/// material gained by a promotion as part of the victim
+fn capture_score(board: &Board, mv: Move) -> i32 {
+    let victim = if mv.is_en_passant() {
+        piece_value(PieceKind::Pawn)
+    } else {
+        board
+            .piece_at(mv.to())
+            .map_or(0, |piece| piece_value(piece.kind()))
+    };
+    let promotion = mv
+        .promotion()
+        .map_or(0, |kind| piece_value(kind) - piece_value(PieceKind::Pawn));
+    let attacker = board
+        .piece_at(mv.from())
+        .map_or(0, |piece| piece_value(piece.kind()));
+    (victim + promotion) * 10 - attacker / 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pick_all(board: &mut Board, mut picker: MovePicker, history: &MoveHistory) -> Vec<String> {
+        let mut moves = Vec::new();
+        while let Some(mv) = picker.next(board, history) {
+            moves.push(mv.to_string());
+        }
+        moves
+    }
+
+    #[test]
+    fn test_picker_yields_every_legal_move_once() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4r2k/8/8/8/8/8/4N3/R3K3 w Q - 0 1",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            let hash_move = board.legal_moves().last().copied();
+            let mut picked = pick_all(
+                &mut board,
+                MovePicker::new(hash_move, 0, None),
+                &MoveHistory::new(),
+            );
+            assert_eq!(picked.first().cloned(), hash_move.map(|mv| mv.to_string()));
+
+            let mut legal: Vec<_> = board.legal_moves().iter().map(Move::to_string).collect();
+            picked.sort();
+            legal.sort();
+            assert_eq!(picked, legal, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_picker_stage_order() {
+        // The queen can take the defended pawn on d5 or the undefended knight on a4
+        let mut board = Board::from_fen("4k3/8/4p3/3p4/n7/8/8/3QK3 w - - 0 1").unwrap();
+        let mut history = MoveHistory::new();
+        let killer = board.parse_uci("e1f2").unwrap();
+        let counter = board.parse_uci("d1h5").unwrap();
+        let previous = board.parse_uci("d1d2").unwrap();
+        history.record_cutoff(&board, 3, 4, counter, Some(previous), &[]);
+        history.record_cutoff(&board, 3, 4, killer, None, &[]);
+        let good_quiet = board.parse_uci("d1g4").unwrap();
+        history.record_cutoff(&board, 10, 8, good_quiet, None, &[]);
+
+        let hash_move = board.parse_uci("e1e2").unwrap();
+        let picked = pick_all(
+            &mut board,
+            MovePicker::new(Some(hash_move), 3, Some(previous)),
+            &history,
+        );
+        assert_eq!(picked[..5], ["e1e2", "d1a4", "e1f2", "d1h5", "d1g4"]);
+        assert_eq!(picked.last().unwrap(), "d1d5");
+    }
+
+    #[test]
+    fn test_picker_captures_only() {
+        let mut board = Board::from_fen("4k3/2P5/4p3/3p4/n7/8/8/3QK3 w - - 0 1").unwrap();
+        let picked = pick_all(&mut board, MovePicker::captures(), &MoveHistory::new());
+        assert_eq!(picked, ["c7c8q", "c7c8r", "d1a4", "c7c8b", "c7c8n"]);
+    }
+
+    #[test]
+    fn test_history_scores() {
+        let board = Board::startpos();
+        let mut history = MoveHistory::new();
+        let good = board.parse_uci("e2e4").unwrap();
+        let bad = board.parse_uci("a2a3").unwrap();
+        for _ in 0..1000 {
+            history.record_cutoff(&board, 0, 20, good, None, &[bad]);
+        }
+        assert!(history.score(PieceColor::White, good) <= MAX_HISTORY);
+        assert!(history.score(PieceColor::White, bad) >= -MAX_HISTORY);
+        assert!(history.score(PieceColor::White, good) > MAX_HISTORY / 2);
+        assert_eq!(history.score(PieceColor::Black, good), 0);
+
+        history.new_search();
+        assert_eq!(history.killers(0), [None; 2]);
+        assert!(history.score(PieceColor::White, good) > 0);
+        history.clear();
+        assert_eq!(history.score(PieceColor::White, good), 0);
+    }
+}
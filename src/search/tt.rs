@@ -0,0 +1,258 @@
+use super::MATE_BOUND;
+use crate::moves::Move;
+use std::mem;
+
+/// How a stored score relates to the true score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The search failed high, the true score is at least the stored one
+    Lower,
+    /// The search failed low, the true score is at most the stored one
+    Upper,
+}
+
+/// A search result stored in the transposition table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    key: u64,
+    best_move: Option<Move>,
+    score: i32,
+    depth: u8,
+    bound: Bound,
+    generation: u8,
+}
+
+impl TtEntry {
+    /// Returns the best move found in the position, if any
+    pub fn best_move(&self) -> Option<Move> {
+        self.best_move
+    }
+
+    /// Returns the score, with mate scores relative to the ply the entry was probed at
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// Returns the remaining depth the position was searched to
+    pub fn depth(&self) -> u32 {
+        self.depth as u32
+    }
+
+    pub fn bound(&self) -> Bound {
+        self.bound
+    }
+}
+
+/// Fixed-size hash table of search results indexed by Zobrist key
+/// Each key maps to a single slot. A new result replaces the stored one if it is for the
+/// same position, if the stored one is from an earlier search, or if it was searched at
+/// least as deep.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    generation: u8,
+}
+
+impl TranspositionTable {
+    /// Creates a table using at most `megabytes` of memory, with at least one entry
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        TranspositionTable {
+            entries: vec![None; Self::capacity_for(megabytes)],
+            generation: 0,
+        }
+    }
+
+    fn capacity_for(megabytes: usize) -> usize {
+        (megabytes * 1024 * 1024 / mem::size_of::<Option<TtEntry>>()).max(1)
+    }
+
+    /// Changes the memory used by the table, clearing it
+    pub fn resize(&mut self, megabytes: usize) {
+        self.entries = vec![None; Self::capacity_for(megabytes)];
+    }
+
+    /// Returns the number of entries the table can hold
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Removes all entries
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+        self.generation = 0;
+    }
+
+    /// Marks the start of a new search, so entries from earlier searches are replaced first
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn index(&self, key: u64) -> usize {
+        ((key as u128 * self.entries.len() as u128) >> 64) as usize
+    }
+
+    /// Looks up the position with the given key, probed at `ply` from the root
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        let mut entry = self.entries[self.index(key)].filter(|entry| entry.key == key)?;
+        entry.score = score_from_table(entry.score, ply);
+        Some(entry)
+    }
+
+    /// Stores a search result for the position with the given key, searched at `ply`
+    /// from the root
+    pub fn store(
+        &mut self,
+        key: u64,
+        ply: usize,
+        depth: u32,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Move>,
+    ) {
+        let index = self.index(key);
+        let generation = self.generation;
+        let depth = depth.min(u8::MAX as u32) as u8;
+
+        if let Some(existing) = self.entries[index]
+            && existing.key != key
+            && existing.generation == generation
+            && existing.depth > depth
+        {
+            return;
+        }
+
+        // Keep the known best move when the new result has none, e.g. after failing low
+        let best_move = best_move.or_else(|| {
+            self.entries[index]
+                .filter(|existing| existing.key == key)
+                .and_then(|existing| existing.best_move)
+        });
+
+        self.entries[index] = Some(TtEntry {
+            key,
+            best_move,
+            score: score_to_table(score, ply),
+            depth,
+            bound,
+            generation,
+        });
+    }
+
+    /// Returns how full the table is in permille, counting entries of the current search
+    /// among the first thousand slots, as reported by the UCI `hashfull` info
+    pub fn hashfull(&self) -> u32 {
+        let sample = self.entries.len().min(1000);
+        let used = self.entries[..sample]
+            .iter()
+            .flatten()
+            .filter(|entry| entry.generation == self.generation)
+            .count();
+        (used * 1000 / sample) as u32
+    }
+}
+
+/// Converts a mate score relative to the root into one relative to the stored position,
+/// so it stays correct when the position is reached at a different ply
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::search::MATE_SCORE;
+
+    fn some_move() -> Move {
+        Board::startpos().parse_uci("e2e4").unwrap()
+    }
+
+    #[test]
+    fn test_tt_store_and_probe() {
+        let mut tt = TranspositionTable::new(1);
+        assert!(tt.capacity() > 1000);
+        assert_eq!(tt.probe(42, 0), None);
+
+        tt.store(42, 0, 5, Bound::Lower, 37, Some(some_move()));
+        let entry = tt.probe(42, 3).unwrap();
+        assert_eq!(entry.depth(), 5);
+        assert_eq!(entry.bound(), Bound::Lower);
+        assert_eq!(entry.score(), 37);
+        assert_eq!(entry.best_move(), Some(some_move()));
+
+        // A result without a move keeps the stored one
+        tt.store(42, 0, 6, Bound::Upper, -10, None);
+        assert_eq!(tt.probe(42, 0).unwrap().best_move(), Some(some_move()));
+
+        tt.clear();
+        assert_eq!(tt.probe(42, 0), None);
+    }
+
+    #[test]
+    fn test_tt_mate_scores() {
+        let mut tt = TranspositionTable::new(1);
+
+        // Mate in 3 plies from a node at ply 2 is mate in 5 plies from the root
+        tt.store(7, 2, 4, Bound::Exact, MATE_SCORE - 5, None);
+        assert_eq!(tt.probe(7, 2).unwrap().score(), MATE_SCORE - 5);
+        assert_eq!(tt.probe(7, 6).unwrap().score(), MATE_SCORE - 9);
+
+        tt.store(8, 1, 4, Bound::Exact, -MATE_SCORE + 4, None);
+        assert_eq!(tt.probe(8, 3).unwrap().score(), -MATE_SCORE + 6);
+    }
+
+    #[test]
+    fn test_tt_replacement() {
+        let mut tt = TranspositionTable::new(0);
+        assert_eq!(tt.capacity(), 1);
+
+        // Deeper results from the current search are kept
+        tt.store(1, 0, 8, Bound::Exact, 10, None);
+        tt.store(2, 0, 3, Bound::Exact, 20, None);
+        assert!(tt.probe(1, 0).is_some());
+        assert!(tt.probe(2, 0).is_none());
+
+        // Results from earlier searches are always replaced
+        tt.new_search();
+        tt.store(2, 0, 3, Bound::Exact, 20, None);
+        assert!(tt.probe(1, 0).is_none());
+        assert_eq!(tt.probe(2, 0).unwrap().score(), 20);
+
+        // The same position is always updated
+        tt.store(2, 0, 1, Bound::Upper, 5, None);
+        assert_eq!(tt.probe(2, 0).unwrap().depth(), 1);
+    }
+
+    #[test]
+    fn test_tt_hashfull() {
+        let mut tt = TranspositionTable::new(1);
+        assert_eq!(tt.hashfull(), 0);
+
+        for key in 0..tt.capacity() as u64 {
+            let key = key.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            tt.store(key, 0, 1, Bound::Exact, 0, None);
+        }
+        assert!(tt.hashfull() > 500);
+
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+    }
+}
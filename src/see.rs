@@ -0,0 +1,153 @@
+use crate::attacks::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks};
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::moves::Move;
+use crate::search::piece_value;
+use crate::types::{PieceColor, PieceKind, Square};
+
+/// Order in which pieces join an exchange, cheapest first
+const EXCHANGE_ORDER: [PieceKind; 6] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+    PieceKind::King,
+];
+
+impl Board {
+    /// Returns the static exchange evaluation of a move in centipawns
+    /// Plays out the captures on the target square with the least valuable attacker each
+    /// time, letting either side stop when continuing would lose material. Sliders
+    /// behind other attackers join as the pieces in front of them capture, but pins and
+    /// checks are ignored.
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castle() {
+            return 0;
+        }
+
+        let to = mv.to();
+        let Some(mover) = self.piece_at(mv.from()) else {
+            return 0;
+        };
+        let mut occupied = self.occupied();
+        occupied.remove(mv.from());
+
+        let mut gains = Vec::with_capacity(32);
+        let mut first_gain = if mv.is_en_passant() {
+            let victim = Square::from_rank_and_file(mv.from().rank(), to.file()).unwrap();
+            occupied.remove(victim);
+            piece_value(PieceKind::Pawn)
+        } else {
+            self.piece_at(to)
+                .map_or(0, |piece| piece_value(piece.kind()))
+        };
+        let mut on_target = mover.kind();
+        if let Some(promotion) = mv.promotion() {
+            first_gain += piece_value(promotion) - piece_value(PieceKind::Pawn);
+            on_target = promotion;
+        }
+        gains.push(first_gain);
+
+        let mut side = mover.color().opposite();
+        loop {
+            let attackers = self.attackers_with(to, occupied) & occupied;
+            let ours = attackers & self.by_color(side);
+            let Some((from, kind)) = EXCHANGE_ORDER.into_iter().find_map(|kind| {
+                (ours & self.by_kind(kind))
+                    .first()
+                    .map(|square| (square, kind))
+            }) else {
+                break;
+            };
+
+            // The king may only capture if the other side cannot recapture
+            if kind == PieceKind::King && !(attackers & self.by_color(side.opposite())).is_empty() {
+                break;
+            }
+
+            gains.push(piece_value(on_target) - gains.last().unwrap());
+            on_target = kind;
+            occupied.remove(from);
+            side = side.opposite();
+        }
+
+        // Each side only continues the exchange if that is better than stopping
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap();
+            let previous = gains.last_mut().unwrap();
+            *previous = -(-*previous).max(last);
+        }
+        gains[0]
+    }
+
+    /// Returns the pieces of both colors attacking `square` when only the squares in
+    /// `occupied` block sliding pieces
+    fn attackers_with(&self, square: Square, occupied: Bitboard) -> Bitboard {
+        let diagonal = self.by_kind(PieceKind::Bishop) | self.by_kind(PieceKind::Queen);
+        let straight = self.by_kind(PieceKind::Rook) | self.by_kind(PieceKind::Queen);
+
+        (pawn_attacks(PieceColor::Black, square) & self.pieces(PieceColor::White, PieceKind::Pawn))
+            | (pawn_attacks(PieceColor::White, square)
+                & self.pieces(PieceColor::Black, PieceKind::Pawn))
+            | (knight_attacks(square) & self.by_kind(PieceKind::Knight))
+            | (king_attacks(square) & self.by_kind(PieceKind::King))
+            | (bishop_attacks(square, occupied) & diagonal)
+            | (rook_attacks(square, occupied) & straight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see(fen: &str, uci: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        board.see(board.parse_uci(uci).unwrap())
+    }
+
+    #[test]
+    fn test_see_simple_exchanges() {
+        // Undefended pawn
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+        // Pawn defended by a pawn
+        assert_eq!(see("4k3/8/3p4/4p3/8/8/4Q3/4K3 w - - 0 1", "e2e5"), -800);
+        // Quiet moves to safe squares and castling are neutral
+        assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "a1a5"), 0);
+        assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1"), 0);
+        // Moving a piece where a pawn takes it
+        assert_eq!(see("4k3/8/3p4/8/8/8/8/2B1K3 w - - 0 1", "c1e3"), 0);
+        assert_eq!(see("4k3/8/3p4/8/8/8/2B5/4K3 w - - 0 1", "c2e4"), 0);
+        assert_eq!(see("4k3/8/3p4/8/8/8/8/4K1N1 w - - 0 1", "g1f3"), 0);
+    }
+
+    #[test]
+    fn test_see_x_rays() {
+        // Knight takes a pawn defended by a knight, with batteries behind both sides
+        assert_eq!(
+            see(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            -220
+        );
+        // Doubled rooks win a pawn defended once
+        assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+    }
+
+    #[test]
+    fn test_see_special_moves() {
+        // En passant wins a pawn unless the capturing pawn is taken back
+        assert_eq!(see("4k3/8/8/2p1pP2/8/8/8/4K3 w - e6 0 1", "f5e6"), 100);
+        assert_eq!(see("4k3/3p4/8/4pP2/8/8/8/4K3 w - e6 0 1", "f5e6"), 0);
+        // Promoting on a defended square loses the new queen
+        assert_eq!(see("3rk3/2P5/8/8/8/8/8/4K3 w - - 0 1", "c7c8q"), -100);
+        assert_eq!(see("3rk3/2P5/8/8/8/8/8/4K3 w - - 0 1", "c7d8q"), 400);
+        // The king cannot recapture a piece defended by an x-ray
+        assert_eq!(see("k3r3/4q3/8/8/8/8/4N3/4K3 b - - 0 1", "e7e2"), 320);
+        assert_eq!(see("k7/4q3/8/8/8/8/4N3/4K3 b - - 0 1", "e7e2"), -580);
+    }
+}
@@ -1,13 +1,18 @@
 use std::fmt::{Display, Error, Formatter};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceColor {
     White,
     Black,
 }
 
 impl PieceColor {
+    /// Returns 0 for White and 1 for Black, for indexing per-color tables
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
     pub fn opposite(self) -> Self {
         match self {
             PieceColor::White => PieceColor::Black,
@@ -16,7 +21,7 @@ impl PieceColor {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceKind {
     King,
     Queen,
@@ -26,7 +31,24 @@ pub enum PieceKind {
     Pawn,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl PieceKind {
+    /// All piece kinds, in the order of their `index`
+    pub const ALL: [PieceKind; 6] = [
+        PieceKind::King,
+        PieceKind::Queen,
+        PieceKind::Rook,
+        PieceKind::Bishop,
+        PieceKind::Knight,
+        PieceKind::Pawn,
+    ];
+
+    /// Returns the position (0-5) of the kind in `PieceKind::ALL`, for indexing per-kind tables
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece {
     color: PieceColor,
     kind: PieceKind,
@@ -47,6 +69,7 @@ impl Piece {
     /// - `B`/`b`: Bishop
     /// - `N`/`n`: Knight
     /// - `P`/`p`: Pawn
+    ///
     /// Returns Option<Piece> which is Some(Piece) if the character is valid, or None if invalid
     pub fn from_fen(fen: char) -> Option<Piece> {
         match fen {
@@ -118,19 +141,31 @@ impl Square {
     }
 
     /// Returns the file (0-7) of the square
-    pub fn file(self) -> u8 {
+    pub const fn file(self) -> u8 {
         self.0 % 8
     }
 
     /// Returns the rank (0-7) of the square
-    pub fn rank(self) -> u8 {
+    pub const fn rank(self) -> u8 {
         self.0 / 8
     }
 
     /// Returns the index (0-63) of the square
-    pub fn index(self) -> usize {
+    pub const fn index(self) -> usize {
         self.0 as usize
     }
+
+    /// Returns the square shifted by the given number of ranks and files,
+    /// or None if that would leave the board
+    pub fn offset(self, rank_delta: i8, file_delta: i8) -> Option<Square> {
+        let rank = self.rank() as i8 + rank_delta;
+        let file = self.file() as i8 + file_delta;
+        if (0..8).contains(&rank) && (0..8).contains(&file) {
+            Square::from_rank_and_file(rank as u8, file as u8)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -149,12 +184,12 @@ impl FromStr for Square {
         let rank_char = s.chars().nth(1).unwrap();
 
         let file = match file_char {
-            'a'..='h' => (file_char as u8) - ('a' as u8),
+            'a'..='h' => (file_char as u8) - b'a',
             _ => return Err(ParseSquareError),
         };
 
         let rank = match rank_char {
-            '1'..='8' => (rank_char as u8) - ('1' as u8),
+            '1'..='8' => (rank_char as u8) - b'1',
             _ => return Err(ParseSquareError),
         };
 
@@ -175,6 +210,45 @@ impl Display for Square {
     }
 }
 
+/// The result of a game, as written in PGN result tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game is still in progress or the result is unknown
+    Ongoing,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseGameResultError;
+
+impl FromStr for GameResult {
+    type Err = ParseGameResultError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Ongoing),
+            _ => Err(ParseGameResultError),
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let token = match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        };
+        write!(f, "{}", token)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,6 +259,15 @@ mod tests {
         assert_eq!(PieceColor::Black.opposite(), PieceColor::White);
     }
 
+    #[test]
+    fn test_color_and_kind_index() {
+        assert_eq!(PieceColor::White.index(), 0);
+        assert_eq!(PieceColor::Black.index(), 1);
+        for (index, kind) in PieceKind::ALL.into_iter().enumerate() {
+            assert_eq!(kind.index(), index);
+        }
+    }
+
     #[test]
     fn test_piece_from_fen() {
         for ch in ['K', 'k', 'Q', 'q', 'R', 'r', 'B', 'b', 'N', 'n', 'P', 'p'] {
@@ -265,6 +348,15 @@ mod tests {
         assert_eq!(square.index(), 37);
     }
 
+    #[test]
+    fn test_square_offset() {
+        let square: Square = "e4".parse().unwrap();
+        assert_eq!(square.offset(1, 0), Some("e5".parse().unwrap()));
+        assert_eq!(square.offset(-2, 1), Some("f2".parse().unwrap()));
+        assert_eq!(square.offset(0, 4), None);
+        assert_eq!(square.offset(-4, 0), None);
+    }
+
     #[test]
     fn test_square_from_str() {
         let square: Square = "e4".parse().unwrap();
@@ -283,4 +375,18 @@ mod tests {
         let square = Square::from_rank_and_file(7, 7).unwrap();
         assert_eq!(square.to_string(), "h8");
     }
+
+    #[test]
+    fn test_game_result_tokens() {
+        for (token, result) in [
+            ("1-0", GameResult::WhiteWins),
+            ("0-1", GameResult::BlackWins),
+            ("1/2-1/2", GameResult::Draw),
+            ("*", GameResult::Ongoing),
+        ] {
+            assert_eq!(token.parse::<GameResult>(), Ok(result));
+            assert_eq!(result.to_string(), token);
+        }
+        assert!("1-1".parse::<GameResult>().is_err());
+    }
 }
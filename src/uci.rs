@@ -0,0 +1,141 @@
+use crate::board::Board;
+use crate::moves::Move;
+use crate::types::{PieceKind, Square};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+};
+
+/// Error returned when a UCI move string cannot be matched to a legal move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseUciError {
+    /// The string is not of the form `<from><to>[promotion]`
+    InvalidSyntax,
+    /// The syntax is valid but no legal move matches it
+    IllegalMove,
+}
+
+impl Display for ParseUciError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            ParseUciError::InvalidSyntax => write!(f, "Invalid UCI move: invalid syntax"),
+            ParseUciError::IllegalMove => write!(f, "Invalid UCI move: illegal move"),
+        }
+    }
+}
+
+impl Error for ParseUciError {}
+
+impl Board {
+    /// Formats a legal move in UCI long algebraic notation, e.g. `e2e4`, `e7e8q` or `e1g1`
+    /// for white castling kingside
+    /// The notation is the same as the `Display` output of `Move`, this method additionally
+    /// checks in debug builds that the move is legal in the position.
+    pub fn uci(&self, mv: Move) -> String {
+        debug_assert!(
+            self.is_legal(mv),
+            "{} is not legal in {}",
+            mv,
+            self.to_fen()
+        );
+        mv.to_string()
+    }
+
+    /// Parses a move in UCI long algebraic notation and returns the matching legal move
+    /// The promotion piece is expected in lowercase, but uppercase is accepted too.
+    pub fn parse_uci(&self, uci: &str) -> Result<Move, ParseUciError> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(ParseUciError::InvalidSyntax);
+        }
+
+        let from: Square = uci[0..2]
+            .parse()
+            .map_err(|_| ParseUciError::InvalidSyntax)?;
+        let to: Square = uci[2..4]
+            .parse()
+            .map_err(|_| ParseUciError::InvalidSyntax)?;
+        let promotion = match uci[4..].chars().next().map(|c| c.to_ascii_lowercase()) {
+            None => None,
+            Some('q') => Some(PieceKind::Queen),
+            Some('r') => Some(PieceKind::Rook),
+            Some('b') => Some(PieceKind::Bishop),
+            Some('n') => Some(PieceKind::Knight),
+            Some(_) => return Err(ParseUciError::InvalidSyntax),
+        };
+
+        self.legal_moves()
+            .into_iter()
+            .find(|mv| mv.from() == from && mv.to() == to && mv.promotion() == promotion)
+            .ok_or(ParseUciError::IllegalMove)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uci() {
+        let board = Board::startpos();
+        let mv = board.parse_uci("e2e4").unwrap();
+        assert!(mv.is_double_push());
+        assert_eq!(board.uci(mv), "e2e4");
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let mv = board.parse_uci("e1g1").unwrap();
+        assert!(mv.is_castle());
+        assert_eq!(board.uci(mv), "e1g1");
+        assert!(board.parse_uci("e1c1").unwrap().is_castle());
+
+        let board = Board::from_fen("1n5k/P7/8/8/8/8/8/7K w - - 0 1").unwrap();
+        let mv = board.parse_uci("a7b8n").unwrap();
+        assert_eq!(mv.promotion(), Some(PieceKind::Knight));
+        assert!(mv.is_capture());
+        assert_eq!(board.uci(mv), "a7b8n");
+        assert_eq!(
+            board.parse_uci("a7a8Q").unwrap().promotion(),
+            Some(PieceKind::Queen)
+        );
+    }
+
+    #[test]
+    fn test_parse_uci_errors() {
+        let board = Board::startpos();
+        for uci in [
+            "", "e2", "e2e", "e2e4e5", "e2e9", "z2e4", "e7e8x", "0000", "é2e4",
+        ] {
+            assert_eq!(
+                board.parse_uci(uci),
+                Err(ParseUciError::InvalidSyntax),
+                "{}",
+                uci
+            );
+        }
+        assert_eq!(board.parse_uci("e2e5"), Err(ParseUciError::IllegalMove));
+        assert_eq!(board.parse_uci("e7e5"), Err(ParseUciError::IllegalMove));
+
+        // A promotion needs the promotion piece, and only promotions may carry one
+        let board = Board::from_fen("1n5k/P7/8/8/8/8/8/7K w - - 0 1").unwrap();
+        assert_eq!(board.parse_uci("a7a8"), Err(ParseUciError::IllegalMove));
+        assert_eq!(board.parse_uci("h1g1q"), Err(ParseUciError::IllegalMove));
+    }
+
+    #[test]
+    fn test_uci_round_trip() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for mv in board.legal_moves() {
+            assert_eq!(board.parse_uci(&board.uci(mv)), Ok(mv));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is not legal")]
+    fn test_uci_rejects_illegal_move() {
+        let mv = Board::startpos().parse_uci("e2e4").unwrap();
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.uci(mv);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::attacks::line;
+use crate::bitboard::Bitboard;
+use crate::board::{Board, ParseFenError, ParseFenErrorKind};
+use crate::types::{Piece, PieceColor, PieceKind, Square};
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+/// A reason a position could not arise in a legal game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositionProblem {
+    /// The color has no king
+    MissingKing(PieceColor),
+    /// The color has more than one king
+    TooManyKings(PieceColor),
+    /// A pawn stands on the first or eighth rank
+    PawnOnBackRank(Square),
+    /// The color has more than 16 pieces
+    TooManyPieces(PieceColor),
+    /// The color has more than 8 pawns
+    TooManyPawns(PieceColor),
+    /// The color has more promoted pieces than it has lost pawns
+    TooManyPromotedPieces(PieceColor),
+    /// The side not to move is in check
+    OpponentInCheck,
+    /// The side to move is checked by more pieces, or in a way, no single move allows
+    ImpossibleCheck,
+    /// The color may castle kingside without its king and rook on their starting squares
+    InvalidKingsideCastling(PieceColor),
+    /// The color may castle queenside without its king and rook on their starting squares
+    InvalidQueensideCastling(PieceColor),
+    /// The en passant square does not follow a double pawn push
+    InvalidEnPassant(Square),
+}
+
+impl PositionProblem {
+    /// Returns the index of the FEN field the problem shows up in
+    fn fen_field(self) -> usize {
+        match self {
+            PositionProblem::OpponentInCheck | PositionProblem::ImpossibleCheck => 1,
+            PositionProblem::InvalidKingsideCastling(_)
+            | PositionProblem::InvalidQueensideCastling(_) => 2,
+            PositionProblem::InvalidEnPassant(_) => 3,
+            _ => 0,
+        }
+    }
+}
+
+impl Display for PositionProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            PositionProblem::MissingKing(color) => write!(f, "{:?} has no king", color),
+            PositionProblem::TooManyKings(color) => write!(f, "{:?} has too many kings", color),
+            PositionProblem::PawnOnBackRank(square) => write!(f, "pawn on back rank {}", square),
+            PositionProblem::TooManyPieces(color) => write!(f, "{:?} has too many pieces", color),
+            PositionProblem::TooManyPawns(color) => write!(f, "{:?} has too many pawns", color),
+            PositionProblem::TooManyPromotedPieces(color) => {
+                write!(f, "{:?} has too many promoted pieces", color)
+            }
+            PositionProblem::OpponentInCheck => write!(f, "side not to move is in check"),
+            PositionProblem::ImpossibleCheck => write!(f, "impossible check"),
+            PositionProblem::InvalidKingsideCastling(color) => {
+                write!(f, "{:?} cannot castle kingside", color)
+            }
+            PositionProblem::InvalidQueensideCastling(color) => {
+                write!(f, "{:?} cannot castle queenside", color)
+            }
+            PositionProblem::InvalidEnPassant(square) => {
+                write!(f, "impossible en passant square {}", square)
+            }
+        }
+    }
+}
+
+impl Board {
+    /// Checks that the position could arise in a legal game, returning every problem found
+    /// An empty list does not prove the position is reachable, only that none of the
+    /// checked rules is broken.
+    pub fn validate(&self) -> Vec<PositionProblem> {
+        let mut problems = Vec::new();
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            match self.pieces(color, PieceKind::King).count() {
+                0 => problems.push(PositionProblem::MissingKing(color)),
+                1 => {}
+                _ => problems.push(PositionProblem::TooManyKings(color)),
+            }
+        }
+
+        let back_ranks = Bitboard::rank(0) | Bitboard::rank(7);
+        for square in self.by_kind(PieceKind::Pawn) & back_ranks {
+            problems.push(PositionProblem::PawnOnBackRank(square));
+        }
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            self.validate_material(color, &mut problems);
+        }
+
+        let kings_valid = !problems.iter().any(|problem| {
+            matches!(
+                problem,
+                PositionProblem::MissingKing(_) | PositionProblem::TooManyKings(_)
+            )
+        });
+        if kings_valid {
+            self.validate_checks(&mut problems);
+        }
+
+        self.validate_castling(&mut problems);
+        self.validate_en_passant(&mut problems);
+
+        problems
+    }
+
+    /// Parses a FEN string like `from_fen`, but also rejects positions that fail
+    /// `validate`, reporting the first problem found
+    pub fn from_fen_strict(fen: &str) -> Result<Board, ParseFenError> {
+        let board = Board::from_fen(fen)?;
+
+        match board.validate().first() {
+            None => Ok(board),
+            Some(&problem) => {
+                let field = problem.fen_field();
+                let offset = field_offset(fen, field);
+                Err(ParseFenError::new(
+                    ParseFenErrorKind::InvalidPosition(problem),
+                    field,
+                    offset,
+                    fen,
+                ))
+            }
+        }
+    }
+
+    fn validate_material(&self, color: PieceColor, problems: &mut Vec<PositionProblem>) {
+        if self.by_color(color).count() > 16 {
+            problems.push(PositionProblem::TooManyPieces(color));
+        }
+
+        let pawns = self.pieces(color, PieceKind::Pawn).count();
+        if pawns > 8 {
+            problems.push(PositionProblem::TooManyPawns(color));
+        }
+
+        // Every piece beyond the starting set must have been a pawn
+        let promoted: u32 = [
+            (PieceKind::Queen, 1),
+            (PieceKind::Rook, 2),
+            (PieceKind::Bishop, 2),
+            (PieceKind::Knight, 2),
+        ]
+        .into_iter()
+        .map(|(kind, initial)| self.pieces(color, kind).count().saturating_sub(initial))
+        .sum();
+        if promoted > 8u32.saturating_sub(pawns) {
+            problems.push(PositionProblem::TooManyPromotedPieces(color));
+        }
+    }
+
+    fn validate_checks(&self, problems: &mut Vec<PositionProblem>) {
+        let us = self.side_to_move();
+        let them_king = self.king_square(us.opposite()).unwrap();
+        if self.is_square_attacked(them_king, us) {
+            problems.push(PositionProblem::OpponentInCheck);
+        }
+
+        let checkers = self.checkers();
+        let impossible = match checkers.count() {
+            0 | 1 => false,
+            2 => {
+                let king = self.king_square(us).unwrap();
+                let mut squares = checkers.squares();
+                let (first, second) = (squares.next().unwrap(), squares.next().unwrap());
+                let is_slider = |square| {
+                    matches!(
+                        self.piece_at(square).map(Piece::kind),
+                        Some(PieceKind::Queen | PieceKind::Rook | PieceKind::Bishop)
+                    )
+                };
+
+                // One of the checks must be discovered, which needs a slider, and the
+                // piece that moved cannot have been on the same line
+                !(is_slider(first) || is_slider(second)) || line(first, second).contains(king)
+            }
+            _ => true,
+        };
+        if impossible {
+            problems.push(PositionProblem::ImpossibleCheck);
+        }
+    }
+
+    fn validate_castling(&self, problems: &mut Vec<PositionProblem>) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            let back_rank = match color {
+                PieceColor::White => 0,
+                PieceColor::Black => 7,
+            };
+            let has = |kind, file| {
+                let square = Square::from_rank_and_file(back_rank, file).unwrap();
+                self.piece_at(square) == Some(Piece::new(color, kind))
+            };
+            let king_home = has(PieceKind::King, 4);
+
+            if self.can_castle_kingside(color) && !(king_home && has(PieceKind::Rook, 7)) {
+                problems.push(PositionProblem::InvalidKingsideCastling(color));
+            }
+            if self.can_castle_queenside(color) && !(king_home && has(PieceKind::Rook, 0)) {
+                problems.push(PositionProblem::InvalidQueensideCastling(color));
+            }
+        }
+    }
+
+    fn validate_en_passant(&self, problems: &mut Vec<PositionProblem>) {
+        let Some(square) = self.en_passant_square() else {
+            return;
+        };
+
+        // The pawn that just moved stands in front of the target square, seen from the
+        // side that pushed it, and the square it came from is empty
+        let them = self.side_to_move().opposite();
+        let forward = match them {
+            PieceColor::White => 1,
+            PieceColor::Black => -1,
+        };
+        let expected_rank = match them {
+            PieceColor::White => 2,
+            PieceColor::Black => 5,
+        };
+        let pushed = square.offset(forward, 0);
+        let origin = square.offset(-forward, 0);
+
+        let valid = square.rank() == expected_rank
+            && self.piece_at(square).is_none()
+            && origin.is_some_and(|origin| self.piece_at(origin).is_none())
+            && pushed.is_some_and(|pushed| {
+                self.piece_at(pushed) == Some(Piece::new(them, PieceKind::Pawn))
+            });
+        if !valid {
+            problems.push(PositionProblem::InvalidEnPassant(square));
+        }
+    }
+}
+
+/// Returns the byte offset where the given whitespace-separated field of a FEN string
+/// starts, or the end of the string if it has fewer fields
+fn field_offset(fen: &str, field: usize) -> usize {
+    let mut fields = 0;
+    let mut previous_whitespace = true;
+    for (offset, c) in fen.char_indices() {
+        if !c.is_whitespace() && previous_whitespace {
+            if fields == field {
+                return offset;
+            }
+            fields += 1;
+        }
+        previous_whitespace = c.is_whitespace();
+    }
+    fen.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(fen: &str) -> Vec<PositionProblem> {
+        Board::from_fen(fen).unwrap().validate()
+    }
+
+    fn square(name: &str) -> Square {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_validate_legal_positions() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K2R b K - 0 1",
+            // Double check from a knight and a discovered rook
+            "4k3/8/3N4/8/8/8/8/3KR3 b - - 0 1",
+            "NNNNNNNN/NN6/8/8/8/8/8/K6k w - - 0 1",
+        ] {
+            assert_eq!(problems(fen), vec![], "{}", fen);
+            assert!(Board::from_fen_strict(fen).is_ok(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_validate_kings_and_pawns() {
+        assert_eq!(
+            problems("8/8/8/8/8/8/8/8 w - - 0 1"),
+            vec![
+                PositionProblem::MissingKing(PieceColor::White),
+                PositionProblem::MissingKing(PieceColor::Black)
+            ]
+        );
+        assert_eq!(
+            problems("k7/8/8/8/8/8/8/K1K1K3 w - - 0 1"),
+            vec![PositionProblem::TooManyKings(PieceColor::White)]
+        );
+        assert_eq!(
+            problems("k6P/8/8/8/8/8/8/K2p4 w - - 0 1"),
+            vec![
+                PositionProblem::PawnOnBackRank(square("d1")),
+                PositionProblem::PawnOnBackRank(square("h8"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_material() {
+        assert_eq!(
+            problems("k7/8/8/8/8/P7/PPPPPPPP/K7 w - - 0 1"),
+            vec![PositionProblem::TooManyPawns(PieceColor::White)]
+        );
+        assert_eq!(
+            problems("7k/8/8/8/8/QQ6/PPPPPPPP/K7 w - - 0 1"),
+            vec![PositionProblem::TooManyPromotedPieces(PieceColor::White)]
+        );
+        assert_eq!(
+            problems("k7/8/8/nnnnnnnn/nnnnnnnn/8/8/K7 b - - 0 1"),
+            vec![
+                PositionProblem::TooManyPieces(PieceColor::Black),
+                PositionProblem::TooManyPromotedPieces(PieceColor::Black)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_checks() {
+        assert_eq!(
+            problems("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1"),
+            vec![PositionProblem::OpponentInCheck]
+        );
+        // Two knights, or two sliders on one line through the king, cannot both check
+        assert_eq!(
+            problems("8/8/8/8/8/3n1n2/8/4K2k w - - 0 1"),
+            vec![PositionProblem::ImpossibleCheck]
+        );
+        assert_eq!(
+            problems("4r3/8/8/8/4K3/8/8/4r2k w - - 0 1"),
+            vec![PositionProblem::ImpossibleCheck]
+        );
+        assert_eq!(
+            problems("4r3/8/8/8/1b6/3n4/8/4K2k w - - 0 1"),
+            vec![PositionProblem::ImpossibleCheck]
+        );
+    }
+
+    #[test]
+    fn test_validate_castling_and_en_passant() {
+        assert_eq!(
+            problems("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1"),
+            vec![
+                PositionProblem::InvalidQueensideCastling(PieceColor::White),
+                PositionProblem::InvalidKingsideCastling(PieceColor::Black)
+            ]
+        );
+        assert_eq!(
+            problems("4k3/8/8/8/8/8/8/R3K2R w KQ e6 0 1"),
+            vec![PositionProblem::InvalidEnPassant(square("e6"))]
+        );
+        assert_eq!(
+            problems("4k3/8/4p3/4p3/8/8/8/4K3 w - e6 0 1"),
+            vec![PositionProblem::InvalidEnPassant(square("e6"))]
+        );
+    }
+
+    #[test]
+    fn test_from_fen_strict() {
+        let fen = "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1";
+        assert!(Board::from_fen(fen).is_ok());
+        let error = Board::from_fen_strict(fen).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            ParseFenErrorKind::InvalidPosition(PositionProblem::OpponentInCheck)
+        );
+        assert_eq!((error.field(), error.offset()), (1, 22));
+
+        let error = Board::from_fen_strict("4k3/8/8/8/8/8/8/4K3 w K - 0 1").unwrap_err();
+        assert_eq!(error.field(), 2);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Invalid FEN: White cannot castle kingside at offset 22\n{}\n{}^",
+                "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+                " ".repeat(22)
+            )
+        );
+
+        let error = Board::from_fen_strict("8/8/8/8/8/8/8/8 w").unwrap_err();
+        assert_eq!((error.field(), error.offset()), (0, 0));
+    }
+}
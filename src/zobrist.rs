@@ -0,0 +1,102 @@
+use crate::types::{Piece, Square};
+
+/// Random keys XORed together to form a position's Zobrist hash
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+}
+
+/// Keys generated at compile time from a fixed seed, so hashes are stable between runs
+static KEYS: ZobristKeys = generate_keys();
+
+/// splitmix64, a small generator with good statistical quality for 64-bit keys
+const fn next_key(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut state = 0x6368_6573_736d_616e;
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        black_to_move: 0,
+        castling: [0; 4],
+        en_passant: [0; 8],
+    };
+
+    let mut color = 0;
+    while color < 2 {
+        let mut kind = 0;
+        while kind < 6 {
+            let mut square = 0;
+            while square < 64 {
+                keys.pieces[color][kind][square] = next_key(&mut state);
+                square += 1;
+            }
+            kind += 1;
+        }
+        color += 1;
+    }
+
+    keys.black_to_move = next_key(&mut state);
+
+    let mut i = 0;
+    while i < 4 {
+        keys.castling[i] = next_key(&mut state);
+        i += 1;
+    }
+
+    let mut file = 0;
+    while file < 8 {
+        keys.en_passant[file] = next_key(&mut state);
+        file += 1;
+    }
+
+    keys
+}
+
+/// Returns the key for a piece standing on a square
+pub(crate) fn piece_key(piece: Piece, square: Square) -> u64 {
+    KEYS.pieces[piece.color().index()][piece.kind().index()][square.index()]
+}
+
+/// Returns the key included when Black is to move
+pub(crate) fn black_to_move_key() -> u64 {
+    KEYS.black_to_move
+}
+
+/// Returns the key for a single castling right, indexed in FEN order `KQkq`
+pub(crate) fn castling_key(right: usize) -> u64 {
+    KEYS.castling[right]
+}
+
+/// Returns the key for an en passant capture being possible on the given file
+pub(crate) fn en_passant_key(file: u8) -> u64 {
+    KEYS.en_passant[file as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_keys_are_unique() {
+        let mut keys = HashSet::new();
+        for color in KEYS.pieces {
+            for kind in color {
+                keys.extend(kind);
+            }
+        }
+        keys.insert(KEYS.black_to_move);
+        keys.extend(KEYS.castling);
+        keys.extend(KEYS.en_passant);
+        assert_eq!(keys.len(), 12 * 64 + 1 + 4 + 8);
+        assert!(!keys.contains(&0));
+    }
+}